@@ -0,0 +1,135 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use gitlab_tools::async_client::AsyncGitlabClient;
+use gitlab_tools::client::MergeRequestParams;
+use gitlab_tools::config::{GitlabConfig, ProjectPolicy};
+use gitlab_tools::merge_request::source_branch;
+use youtrack_tools::rest_api::client::{YoutrackClient, YoutrackClientImpl};
+use youtrack_tools::rest_api::error::YoutrackError;
+use youtrack_tools::rest_api::json_models::issue::IssueDto;
+use youtrack_tools::rest_api::json_models::issue::field::IssueStateType;
+use youtrack_tools::rest_api::markdown::{self, issue_mention};
+use youtrack_tools::rest_api::service::issues::{fetch_custom_fields, set_simple_field};
+use crate::webhook::{EventHandler, HandlerResult, StateChange};
+
+/// `SSP-7: <summary>` from `source_branch` to the target branch of the policy, description ends with a link
+/// back to the issue and the issue assignee becomes the assignee if gitlab has such a user
+pub fn merge_request_params(issue: &IssueDto, policy: &ProjectPolicy, youtrack_url: &str) -> MergeRequestParams {
+    let issue_id = issue.id_readable.clone().unwrap_or_else(|| issue.id.clone());
+    let summary = issue.summary.clone().unwrap_or_default();
+    let issue_url = format!("{}/issue/{}", youtrack_url.trim_end_matches('/'), issue_id);
+
+    let back_link = format!("Youtrack: {}", markdown::link(&issue_mention(&issue_id), &issue_url));
+    let description = match issue.description.as_deref().map(str::trim).filter(|description| !description.is_empty()) {
+        Some(description) => format!("{}\n\n---\n{}", description, back_link),
+        None => back_link,
+    };
+
+    let mut params = MergeRequestParams::new(
+        &policy.gitlab_project,
+        &format!("{}: {}", issue_id, summary),
+        &source_branch(&policy.branch_prefix, &issue_id, &summary),
+        &policy.target_branch,
+    );
+    params.description = description;
+    params.labels = issue.tags.iter().map(|tag| tag.name.clone()).collect();
+    params.assignee_username = issue.assignee_login().map(|login| policy.gitlab_username(login).to_string());
+    params
+}
+
+/// Creates `feature/SSP-7-short-summary` from the target branch for an issue which is In Progress
+/// and writes the name to the branch field of the policy. Issues in other states and issues with the field
+/// already filled are skipped, so it's safe to call on every state change. Returns the created branch
+pub async fn start_issue_branch(youtrack: &YoutrackClientImpl, gitlab: &AsyncGitlabClient, policy: &ProjectPolicy, issue_id: &str)
+                                -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let issue = youtrack.issue(issue_id.to_string()).await?;
+    let state = issue.get_state().map(|state| IssueStateType::new(&state.state_name()));
+    if !matches!(state, Some(IssueStateType::InProgress)) {
+        return Ok(None);
+    }
+    create_issue_branch(youtrack, gitlab, policy, &issue).await
+}
+
+/// `start_issue_branch` without the state check, for callers which know the issue went In Progress.
+/// Issues with the branch field already filled are skipped
+pub async fn create_issue_branch(youtrack: &YoutrackClientImpl, gitlab: &AsyncGitlabClient, policy: &ProjectPolicy, issue: &IssueDto)
+                                 -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let issue_id = issue.id_readable.clone().unwrap_or_else(|| issue.id.clone());
+    let fields = fetch_custom_fields(youtrack.http_client(), &issue_id).await?;
+    let branch_field = fields.iter().find(|field| field.name == policy.branch_field)
+        .ok_or_else(|| YoutrackError::FieldNotFound { project: policy.youtrack_project.clone(), field: policy.branch_field.clone() })?;
+    if matches!(branch_field.value.as_str(), Some(branch) if !branch.trim().is_empty()) {
+        return Ok(None);
+    }
+
+    let branch = source_branch(&policy.branch_prefix, &issue_id, issue.summary.as_deref().unwrap_or_default());
+    if !gitlab.branch_exists(&policy.gitlab_project, &branch).await? {
+        gitlab.create_branch(&policy.gitlab_project, &branch, &policy.target_branch).await?;
+        if policy.protect_branches {
+            gitlab.protect_branch(&policy.gitlab_project, &branch).await?;
+        }
+    }
+
+    set_simple_field(youtrack.http_client(), &issue_id, branch_field, Some(&branch)).await?;
+    log::info!("Branch {} of {} created in {}", branch, issue_id, policy.gitlab_project);
+    Ok(Some(branch))
+}
+
+/// Starts branches of issues which go In Progress, the state changes are posted by the youtrack workflow
+pub struct IssueBranches {
+    youtrack: Arc<YoutrackClientImpl>,
+    gitlab: AsyncGitlabClient,
+    config: GitlabConfig,
+}
+
+impl IssueBranches {
+    pub fn new(youtrack: Arc<YoutrackClientImpl>, gitlab: AsyncGitlabClient, config: GitlabConfig) -> Self {
+        IssueBranches { youtrack, gitlab, config }
+    }
+}
+
+#[async_trait]
+impl EventHandler for IssueBranches {
+    async fn state_change(&self, change: &StateChange) -> HandlerResult {
+        let policy = match started_policy(&self.config, change) {
+            Some(policy) => policy,
+            None => return Ok(()),
+        };
+        // the workflow posts before the change is saved, youtrack still returns the old state
+        let issue = self.youtrack.issue(change.issue.clone()).await?;
+        if create_issue_branch(&self.youtrack, &self.gitlab, policy, &issue).await?.is_none() {
+            log::info!("{} already has a branch", change.issue);
+        }
+        Ok(())
+    }
+}
+
+/// Policy of the issue when it went In Progress, `None` for other states and issues of unknown projects
+fn started_policy<'a>(config: &'a GitlabConfig, change: &StateChange) -> Option<&'a ProjectPolicy> {
+    if !matches!(IssueStateType::new(&change.state), IssueStateType::InProgress) {
+        return None;
+    }
+    config.policy(&change.issue)
+}
+
+#[cfg(test)]
+mod tests {
+    use gitlab_tools::config::{GitlabConfig, ProjectPolicy};
+    use crate::branch::started_policy;
+    use crate::webhook::StateChange;
+
+    fn transition(payload: &str) -> StateChange {
+        serde_json::from_str(payload).unwrap()
+    }
+
+    #[test]
+    fn branch_is_started_by_in_progress() {
+        let mut config = GitlabConfig::new("https://git.let4.me", "token");
+        config.projects.push(ProjectPolicy::new("SSP", "serge/proj2"));
+
+        let started = transition(r#"{"issue": "SSP-7", "state": "In Progress", "oldState": "Open"}"#);
+        assert_eq!(started_policy(&config, &started).map(|policy| policy.gitlab_project.as_str()), Some("serge/proj2"));
+        assert!(started_policy(&config, &transition(r#"{"issue": "SSP-7", "state": "Fixed", "oldState": "In Progress"}"#)).is_none());
+        assert!(started_policy(&config, &transition(r#"{"issue": "WEB-3", "state": "In Progress"}"#)).is_none());
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use gitlab_tools::config::GitlabConfig;
+use gitlab_tools::events::{NoteEvent, NoteMergeRequest};
+use git_utils::issue_keys::IssueKeyParser;
+use youtrack_tools::rest_api::client::YoutrackClientImpl;
+use youtrack_tools::rest_api::markdown::{escape, from_gitlab, inline_code, merge_request_link, Markdown};
+use youtrack_tools::rest_api::service::comments::{add_comment, delete_comment, update_comment};
+use crate::webhook::{EventHandler, HandlerResult};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MirroredComment {
+    pub issue_id: String,
+    pub comment_id: String,
+}
+
+/// Gitlab note id to the youtrack comments which mirror it, one per linked issue.
+/// With a file the mapping survives restarts, notes edited later are still found
+pub struct MirroredNotes {
+    notes: Mutex<HashMap<u64, Vec<MirroredComment>>>,
+    file: Option<PathBuf>,
+}
+
+impl MirroredNotes {
+    pub fn in_memory() -> Self {
+        MirroredNotes { notes: Mutex::new(HashMap::new()), file: None }
+    }
+
+    /// JSON object of note ids, the file is created by the first mirrored note
+    pub fn load(file: PathBuf) -> Result<Self, Box<dyn std::error::Error>> {
+        let notes = match std::fs::read(&file) {
+            Ok(content) => serde_json::from_slice(&content)?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(error) => return Err(error.into()),
+        };
+        Ok(MirroredNotes { notes: Mutex::new(notes), file: Some(file) })
+    }
+
+    pub fn get(&self, note_id: u64) -> Vec<MirroredComment> {
+        self.notes.lock().unwrap().get(&note_id).cloned().unwrap_or_default()
+    }
+
+    pub fn insert(&self, note_id: u64, comments: Vec<MirroredComment>) -> std::io::Result<()> {
+        let mut notes = self.notes.lock().unwrap();
+        notes.insert(note_id, comments);
+        self.save(&notes)
+    }
+
+    pub fn remove(&self, note_id: u64) -> std::io::Result<Vec<MirroredComment>> {
+        let mut notes = self.notes.lock().unwrap();
+        let comments = notes.remove(&note_id).unwrap_or_default();
+        self.save(&notes)?;
+        Ok(comments)
+    }
+
+    fn save(&self, notes: &HashMap<u64, Vec<MirroredComment>>) -> std::io::Result<()> {
+        match &self.file {
+            Some(file) => std::fs::write(file, serde_json::to_vec(notes)?),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Mirrors notes of merge requests to comments of the issues the merge request mentions
+/// (title, description, source branch), so discussions are seen by people who read only youtrack.
+/// Edited notes update their comments, deleted notes delete them. System notes aren't mirrored
+pub struct Discussions {
+    youtrack: Arc<YoutrackClientImpl>,
+    config: GitlabConfig,
+    notes: MirroredNotes,
+}
+
+impl Discussions {
+    pub fn new(youtrack: Arc<YoutrackClientImpl>, config: GitlabConfig, notes: MirroredNotes) -> Self {
+        Discussions { youtrack, config, notes }
+    }
+}
+
+#[async_trait]
+impl EventHandler for Discussions {
+    async fn note(&self, event: &NoteEvent) -> HandlerResult {
+        let attributes = &event.object_attributes;
+        let merge_request = match &event.merge_request {
+            Some(merge_request) if !attributes.system && attributes.noteable_type == "MergeRequest" => merge_request,
+            _ => return Ok(()),
+        };
+        let client = self.youtrack.http_client();
+
+        if matches!(attributes.action.as_deref(), Some("delete") | Some("destroy")) {
+            for comment in self.notes.remove(attributes.id)? {
+                delete_comment(client, &comment.issue_id, &comment.comment_id).await?;
+            }
+            return Ok(());
+        }
+
+        let text = note_comment(event, merge_request);
+        // gitlab without `action` sends edits as new notes, the mapping tells them apart
+        let mirrored = self.notes.get(attributes.id);
+        if !mirrored.is_empty() {
+            for comment in mirrored {
+                update_comment(client, &comment.issue_id, &comment.comment_id, &text).await?;
+            }
+            return Ok(());
+        }
+
+        let keys = IssueKeyParser::new(self.config.policies_of(&event.project.path_with_namespace).map(|policy| &policy.youtrack_project));
+        let issue_ids = keys.issue_ids(&[merge_request.title.as_str(), merge_request.description.as_deref().unwrap_or_default(), merge_request.source_branch.as_str()]);
+        let mut comments = Vec::new();
+        for issue_id in issue_ids {
+            match add_comment(client, &issue_id, &text).await {
+                Ok(key) => comments.push(MirroredComment { issue_id, comment_id: key.id }),
+                Err(error) => log::error!("Note {} isn't mirrored to {}: {}", attributes.id, issue_id, error),
+            }
+        }
+        if !comments.is_empty() {
+            self.notes.insert(attributes.id, comments)?;
+        }
+        Ok(())
+    }
+}
+
+/// `**Anna** commented [!12 Title](note url) at src/login.rs:42` and the note with gitlab references resolved
+fn note_comment(event: &NoteEvent, merge_request: &NoteMergeRequest) -> String {
+    let attributes = &event.object_attributes;
+    let mut header = format!("**{}** commented {}", escape(&event.user.name), merge_request_link(merge_request.iid, &merge_request.title, &attributes.url));
+    if let Some(location) = attributes.position.as_ref().and_then(|position| position.location()) {
+        header.push_str(&format!(" at {}", inline_code(&location)));
+    }
+    Markdown::new()
+        .raw(&header)
+        .raw(&from_gitlab(&attributes.note, &event.project.web_url))
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use gitlab_tools::events::{samples, NotePosition};
+    use crate::discussions::{note_comment, MirroredComment, MirroredNotes};
+
+    #[test]
+    fn comment_of_note() {
+        let mut event = samples::note();
+        event.object_attributes.position = Some(NotePosition { old_path: None, new_path: Some("src/login.rs".to_string()), old_line: None, new_line: Some(42) });
+        let merge_request = event.merge_request.clone().unwrap();
+        assert_eq!(note_comment(&event, &merge_request), format!(
+            "**Anna** commented [!12 {}](https://git.let4.me/serge/proj2/-/merge_requests/12#note_1244) at `src/login.rs:42`\n\n\
+            Please keep `#34` in mind, see [!11](https://git.let4.me/serge/proj2/-/merge_requests/11) as well", merge_request.title));
+    }
+
+    #[test]
+    fn mapping_survives_restart() {
+        let file = std::env::temp_dir().join(format!("mirrored-notes-{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&file);
+        let comment = MirroredComment { issue_id: "SSP-7".to_string(), comment_id: "4-17".to_string() };
+        MirroredNotes::load(file.clone()).unwrap().insert(1244, vec![comment.clone()]).unwrap();
+
+        let notes = MirroredNotes::load(file.clone()).unwrap();
+        assert_eq!(notes.get(1244), vec![comment.clone()]);
+        assert_eq!(notes.remove(1244).unwrap(), vec![comment]);
+        assert!(MirroredNotes::load(file.clone()).unwrap().get(1244).is_empty());
+        std::fs::remove_file(&file).unwrap();
+    }
+}
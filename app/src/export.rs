@@ -0,0 +1,21 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use youtrack_tools::rest_api::client::YoutrackClientImpl;
+use youtrack_tools::rest_api::export::{export_issues, ExportFormat, ExportOptions};
+
+/// `web-app export <jsonl|csv> <query> [file]`, writes to stdout when file is omitted
+pub async fn export_command(youtrack: &YoutrackClientImpl, format: &str, query: &str, output: Option<&str>)
+                            -> Result<usize, Box<dyn std::error::Error>> {
+    let format = match format {
+        "jsonl" => ExportFormat::JsonLines,
+        "csv" => ExportFormat::Csv,
+        other => return Err(format!("unknown export format {}, expected jsonl or csv", other).into()),
+    };
+    let writer: Box<dyn Write> = match output {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(std::io::stdout()),
+    };
+    let exported = export_issues(youtrack.http_client(), &ExportOptions::new(query, format), writer).await?;
+    log::info!("{} issues exported for \"{}\"", exported, query);
+    Ok(exported)
+}
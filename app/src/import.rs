@@ -0,0 +1,41 @@
+use std::fs::File;
+use std::io::BufReader;
+use youtrack_tools::rest_api::client::YoutrackClientImpl;
+use youtrack_tools::rest_api::import::{import_issues, read_csv, read_jsonl, ImportOptions, KeyStorage};
+
+/// Field keeping the key of the migrated issue when neither `--field` nor `--tag` is given
+const DEFAULT_KEY_FIELD: &str = "External ID";
+
+/// `web-app import <jsonl|csv> <project> <file> [--dry-run] [--field <name> | --tag <prefix>]`
+pub async fn import_command(youtrack: &YoutrackClientImpl, format: &str, project: &str, path: &str, flags: &[&str])
+                            -> Result<(), Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(path)?);
+    let specs = match format {
+        "jsonl" => read_jsonl(reader)?,
+        "csv" => read_csv(reader)?,
+        other => return Err(format!("unknown import format {}, expected jsonl or csv", other).into()),
+    };
+
+    let mut options = ImportOptions {
+        project: project.to_string(),
+        key_storage: KeyStorage::Field(DEFAULT_KEY_FIELD.to_string()),
+        dry_run: false,
+    };
+    let mut flags = flags.iter();
+    while let Some(flag) = flags.next() {
+        match *flag {
+            "--dry-run" => options.dry_run = true,
+            "--field" => options.key_storage = KeyStorage::Field(flag_value(flag, flags.next())?),
+            "--tag" => options.key_storage = KeyStorage::Tag(flag_value(flag, flags.next())?),
+            other => return Err(format!("unknown import option {}", other).into()),
+        }
+    }
+
+    let report = import_issues(youtrack.http_client(), &specs, &options).await?;
+    print!("{}", report);
+    Ok(())
+}
+
+fn flag_value(flag: &str, value: Option<&&str>) -> Result<String, String> {
+    value.map(|value| value.to_string()).ok_or_else(|| format!("{} requires a value", flag))
+}
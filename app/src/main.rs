@@ -1,9 +1,35 @@
+use std::sync::Arc;
 use log::LevelFilter;
 use env_logger::WriteStyle;
-use youtrack_tools::rest_api::client::YoutrackClient;
-use youtrack_tools::rest_api::base::ops::BaseOps;
-use youtrack_tools::rest_api::json_models::issue::field::IssueStateType;
+use youtrack_tools::rest_api::client::{YoutrackClient, YoutrackClientImpl};
 use indoc::indoc;
+use crate::settings::Settings;
+use gitlab_tools::async_client::AsyncGitlabClient;
+
+mod release;
+mod settings;
+mod export;
+mod import;
+mod branch;
+mod webhook;
+mod merged;
+mod smart_commits;
+mod pipelines;
+mod discussions;
+
+/// Printed when the arguments match no command
+const USAGE: &str = indoc!("
+    Usage: web-app <command>
+
+    Commands:
+        serve                                                    receive gitlab hooks and youtrack state changes, see the webhook section of settings.yaml
+        merge-request <issue>                                    open the merge request of the issue branch
+        start-branch <issue>                                     create the branch of an issue In Progress
+        release <youtrack project> <gitlab project> <tag>        create the version and add it to the issues of the tag
+        export <jsonl|csv> <query> [file]                        write the found issues to the file or stdout
+        import <jsonl|csv> <project> <file> [--dry-run] [--field <name> | --tag <prefix>]
+                                                                 create or update issues by their external key
+");
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -11,20 +37,71 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .filter(None, LevelFilter::Info)
         .write_style(WriteStyle::Always)
         .init();
-    ;
-
-    let token = "perm:c2VnZQ==.NDUtMA==.BwWbdAkDk6Dr6rKnoVD3Rg5PStkMFE".to_string();
-    let host = "http://localhost:10100".to_string();
-    let client_impl = youtrack_tools::rest_api::client::YoutrackClientImpl::new(host, token).await.unwrap();
-
-    let mut issue = client_impl.issue("SSP-7".to_string()).await;
-    issue.set_state(IssueStateType::ToVerify);
-    let new_description_text = indoc!("### New description 3
-                                Bugaga **from** __rust__
-                                [lol](google.com)");
-    issue.description = Some(new_description_text.to_string());
 
-    let x = issue.save().await;
+    let settings = Settings::load()?;
+    let config = settings.youtrack.config()?;
+    let client_impl = YoutrackClientImpl::from_config(config);
 
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["release", youtrack_project, gitlab_project, tag] => {
+            let gitlab = AsyncGitlabClient::new(settings.gitlab()?).await?;
+            release::release_tag(&client_impl, &gitlab, youtrack_project, gitlab_project, tag).await?;
+        }
+        ["merge-request", issue_id] => {
+            let gitlab_config = settings.gitlab()?;
+            let gitlab = AsyncGitlabClient::new(gitlab_config).await?;
+            let policy = gitlab_config.policy(issue_id)
+                .ok_or_else(|| format!("no gitlab project for {} in settings.yaml", issue_id))?;
+            let issue = client_impl.issue(issue_id.to_string()).await?;
+            let merge_request = gitlab.create_merge_request(&branch::merge_request_params(&issue, policy, &settings.youtrack.url)).await?;
+            log::info!("Merge request !{} created: {}", merge_request.iid, merge_request.web_url);
+        }
+        ["serve"] => {
+            let webhook_settings = settings.webhook()?;
+            let mut webhook = webhook::Webhook::new(webhook_settings.secret()?)
+                .with_handler(Arc::new(webhook::LogHandler));
+            if let Some(gitlab_config) = &settings.gitlab {
+                let youtrack = Arc::new(client_impl);
+                let gitlab = AsyncGitlabClient::new(gitlab_config).await?;
+                let processed = match &webhook_settings.processed_commits {
+                    Some(file) => smart_commits::ProcessedCommits::load(file.clone())?,
+                    None => smart_commits::ProcessedCommits::in_memory(),
+                };
+                let notes = match &webhook_settings.mirrored_notes {
+                    Some(file) => discussions::MirroredNotes::load(file.clone())?,
+                    None => discussions::MirroredNotes::in_memory(),
+                };
+                webhook = webhook
+                    .with_handler(Arc::new(branch::IssueBranches::new(youtrack.clone(), gitlab.clone(), gitlab_config.clone())))
+                    .with_handler(Arc::new(merged::MergedIssues::new(youtrack.clone(), gitlab, gitlab_config.clone())))
+                    .with_handler(Arc::new(smart_commits::SmartCommits::new(youtrack.clone(), gitlab_config.clone(), processed)))
+                    .with_handler(Arc::new(pipelines::PipelineStatus::new(youtrack.clone(), gitlab_config.clone())))
+                    .with_handler(Arc::new(discussions::Discussions::new(youtrack, gitlab_config.clone(), notes)));
+            }
+            webhook::serve(webhook, webhook_settings.listen.parse()?).await?;
+        }
+        ["start-branch", issue_id] => {
+            let gitlab_config = settings.gitlab()?;
+            let gitlab = AsyncGitlabClient::new(gitlab_config).await?;
+            let policy = gitlab_config.policy(issue_id)
+                .ok_or_else(|| format!("no gitlab project for {} in settings.yaml", issue_id))?;
+            let branch = branch::start_issue_branch(&client_impl, &gitlab, policy, issue_id).await
+                .map_err(|error| error as Box<dyn std::error::Error>)?;
+            if branch.is_none() {
+                log::info!("{} isn't In Progress or already has a branch", issue_id);
+            }
+        }
+        ["export", format, query, ref output @ ..] => {
+            export::export_command(&client_impl, format, query, output.first().copied()).await?;
+        }
+        ["import", format, project, path, ref flags @ ..] => {
+            import::import_command(&client_impl, format, project, path, flags).await?;
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,109 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use gitlab_tools::async_client::AsyncGitlabClient;
+use gitlab_tools::config::{GitlabConfig, ProjectPolicy};
+use gitlab_tools::events::MergeRequestEvent;
+use git_utils::issue_keys::{Intent, IssueKeyParser};
+use youtrack_tools::rest_api::client::YoutrackClientImpl;
+use youtrack_tools::rest_api::json_models::issue::{RawCustomField, STATE_FIELD};
+use youtrack_tools::rest_api::markdown::{inline_code, merge_request_link};
+use youtrack_tools::rest_api::service::commands::{command_value, execute_command, Command};
+use youtrack_tools::rest_api::service::comments::add_comment;
+use youtrack_tools::rest_api::service::issues::fetch_custom_fields;
+use crate::webhook::{EventHandler, HandlerResult};
+
+
+/// Moves issues mentioned by a merged merge request to the state of `ProjectPolicy::merge_transitions`
+/// and comments them with the merge request link
+pub struct MergedIssues {
+    youtrack: Arc<YoutrackClientImpl>,
+    gitlab: AsyncGitlabClient,
+    config: GitlabConfig,
+}
+
+impl MergedIssues {
+    pub fn new(youtrack: Arc<YoutrackClientImpl>, gitlab: AsyncGitlabClient, config: GitlabConfig) -> Self {
+        MergedIssues { youtrack, gitlab, config }
+    }
+
+    /// Issues which are only referenced (`refs SSP-8`) get the comment without the transition
+    async fn close_issue(&self, policy: &ProjectPolicy, issue_id: &str, referenced_only: bool, comment: &str) -> HandlerResult {
+        let client = self.youtrack.http_client();
+        let fields = fetch_custom_fields(client, issue_id).await?;
+        let state = fields.iter()
+            .find(|field| field.name == STATE_FIELD)
+            .and_then(RawCustomField::value_name)
+            .unwrap_or_default();
+        match policy.merged_state(state) {
+            Some(merged_state) if merged_state != state && !referenced_only => {
+                let query = format!("{} {}", STATE_FIELD, command_value(merged_state));
+                let mut command = Command::new(query, &[issue_id.to_string()]);
+                command.comment = Some(comment.to_string());
+                execute_command(client, command).await?;
+                log::info!("{} moved from {} to {}", issue_id, state, merged_state);
+            }
+            _ => {
+                add_comment(client, issue_id, comment).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventHandler for MergedIssues {
+    async fn merge_request(&self, event: &MergeRequestEvent) -> HandlerResult {
+        if !event.is_merged() {
+            return Ok(());
+        }
+        let attributes = &event.object_attributes;
+        let project = event.project.path_with_namespace.as_str();
+        let commits = self.gitlab.merge_request_commits(project, attributes.iid).await?;
+
+        let mut texts = vec![attributes.title.as_str(), attributes.description.as_deref().unwrap_or_default(), attributes.source_branch.as_str()];
+        texts.extend(commits.iter().map(|commit| commit.message.as_str()));
+        texts.extend(attributes.last_commit.iter().map(|commit| commit.message.as_str()));
+        let comment = merged_comment(event);
+
+        let policies: Vec<&ProjectPolicy> = self.config.policies_of(project).collect();
+        let parser = IssueKeyParser::new(policies.iter().map(|policy| &policy.youtrack_project));
+        let keys: Vec<_> = texts.iter().flat_map(|text| parser.parse(text)).collect();
+        for issue_id in parser.issue_ids(&texts) {
+            let mentions: Vec<_> = keys.iter().filter(|key| key.id() == issue_id).collect();
+            let referenced_only = mentions.iter().all(|key| key.intent == Intent::Reference);
+            let policy = match policies.iter().find(|policy| policy.youtrack_project.eq_ignore_ascii_case(&mentions[0].project)) {
+                Some(policy) => policy,
+                None => continue,
+            };
+            if let Err(error) = self.close_issue(policy, &issue_id, referenced_only, &comment).await {
+                log::error!("{} isn't updated after the merge of !{}: {}", issue_id, attributes.iid, error);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `Merged in [!12 Title](url) into master, merge commit 2b4d1c5b`
+fn merged_comment(event: &MergeRequestEvent) -> String {
+    let attributes = &event.object_attributes;
+    let mut comment = format!("Merged in {} into {}", merge_request_link(attributes.iid, &attributes.title, &attributes.url), inline_code(&attributes.target_branch));
+    let sha = attributes.merge_commit_sha.as_deref()
+        .or_else(|| attributes.last_commit.as_ref().map(|commit| commit.id.as_str()));
+    if let Some(sha) = sha {
+        comment.push_str(&format!(", merge commit {}", inline_code(&sha[..sha.len().min(8)])));
+    }
+    comment
+}
+
+#[cfg(test)]
+mod tests {
+    use gitlab_tools::events::samples;
+    use crate::merged::merged_comment;
+
+    #[test]
+    fn comment_of_merge() {
+        let comment = merged_comment(&samples::merge_request());
+        assert!(comment.starts_with("Merged in [!12 "), "{}", comment);
+        assert!(comment.ends_with("into `master`, merge commit `2b4d1c5b`"), "{}", comment);
+    }
+}
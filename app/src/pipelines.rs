@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use async_trait::async_trait;
+use gitlab_tools::config::{GitlabConfig, ProjectPolicy};
+use gitlab_tools::events::PipelineEvent;
+use git_utils::issue_keys::IssueKeyParser;
+use youtrack_tools::rest_api::client::YoutrackClientImpl;
+use youtrack_tools::rest_api::error::YoutrackError;
+use youtrack_tools::rest_api::json_models::issue::{RawCustomField, STATE_FIELD};
+use youtrack_tools::rest_api::markdown::{inline_code, link, Markdown};
+use youtrack_tools::rest_api::service::commands::{command_value, execute_command, Command};
+use youtrack_tools::rest_api::service::comments::add_comment;
+use youtrack_tools::rest_api::service::issues::{fetch_custom_fields, set_simple_field};
+use crate::webhook::{EventHandler, HandlerResult};
+
+/// Field types which are set by value, the others are set by commands
+const SIMPLE_FIELD_TYPES: &[&str] = &["SimpleIssueCustomField", "TextIssueCustomField"];
+
+/// Writes results of finished pipelines to youtrack: the build field and tags of the issue of a branch
+/// (`feature/SSP-7-...`), a comment with the failed jobs, and reopening of merged issues when the default
+/// branch fails. What is done is configured by `build_field`, `build_tags` and `reopen_state` of the policy
+pub struct PipelineStatus {
+    youtrack: Arc<YoutrackClientImpl>,
+    config: GitlabConfig,
+}
+
+impl PipelineStatus {
+    pub fn new(youtrack: Arc<YoutrackClientImpl>, config: GitlabConfig) -> Self {
+        PipelineStatus { youtrack, config }
+    }
+
+    async fn update_issue(&self, policy: &ProjectPolicy, issue_id: &str, passed: bool, default_branch: bool, comment: Option<&str>) -> HandlerResult {
+        let client = self.youtrack.http_client();
+        let fields = fetch_custom_fields(client, issue_id).await?;
+        let status = if passed { "passed" } else { "failed" };
+        let mut query = Vec::new();
+
+        if default_branch {
+            let state = fields.iter()
+                .find(|field| field.name == STATE_FIELD)
+                .and_then(RawCustomField::value_name)
+                .unwrap_or_default();
+            if let Some(reopen_state) = policy.reopen_state.as_deref().filter(|_| !passed && policy.is_merged_state(state)) {
+                query.push(format!("{} {}", STATE_FIELD, command_value(reopen_state)));
+                log::info!("{} is reopened, the pipeline of the default branch failed", issue_id);
+            }
+        } else {
+            if let Some(build_field) = &policy.build_field {
+                let field = fields.iter().find(|field| &field.name == build_field)
+                    .ok_or_else(|| YoutrackError::FieldNotFound { project: policy.youtrack_project.clone(), field: build_field.clone() })?;
+                if SIMPLE_FIELD_TYPES.contains(&field.field_type.as_str()) {
+                    set_simple_field(client, issue_id, field, Some(status)).await?;
+                } else {
+                    query.push(format!("{} {}", command_value(build_field), status));
+                }
+            }
+            if policy.build_tags {
+                let previous = if passed { "failed" } else { "passed" };
+                query.push(format!("tag build-{} untag build-{}", status, previous));
+            }
+        }
+
+        match (query.is_empty(), comment) {
+            (false, _) => {
+                let mut command = Command::new(query.join(" "), &[issue_id.to_string()]);
+                command.comment = comment.map(str::to_string);
+                command.silent = passed;
+                execute_command(client, command).await?;
+            }
+            (true, Some(comment)) => {
+                add_comment(client, issue_id, comment).await?;
+            }
+            (true, None) => {}
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventHandler for PipelineStatus {
+    async fn pipeline(&self, event: &PipelineEvent) -> HandlerResult {
+        let passed = match event.object_attributes.status.as_str() {
+            "success" => true,
+            "failed" => false,
+            _ => return Ok(()),
+        };
+        let branch = match event.branch() {
+            Some(branch) => branch,
+            None => return Ok(()),
+        };
+        let default_branch = event.merge_request.is_none() && event.project.default_branch.as_deref() == Some(branch);
+        if default_branch && passed {
+            return Ok(());
+        }
+
+        let policies: Vec<&ProjectPolicy> = self.config.policies_of(&event.project.path_with_namespace).collect();
+        let keys = IssueKeyParser::new(policies.iter().map(|policy| &policy.youtrack_project));
+        // the default branch has no issue of its own, the issues of the merged commit are checked
+        let issue_ids = if default_branch {
+            keys.issue_ids(&[event.commit.as_ref().map(|commit| commit.message.as_str()).unwrap_or_default()])
+        } else {
+            keys.issue_ids(&[branch])
+        };
+        let comment = if passed { None } else { Some(failure_comment(event)) };
+
+        for issue_id in issue_ids {
+            let policy = match policies.iter().find(|policy| matches!(issue_id.split('-').next(), Some(project) if policy.youtrack_project.eq_ignore_ascii_case(project))) {
+                Some(policy) => policy,
+                None => continue,
+            };
+            if let Err(error) = self.update_issue(policy, &issue_id, passed, default_branch, comment.as_deref()).await {
+                log::error!("Pipeline {} isn't written to {}: {}", event.object_attributes.id, issue_id, error);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `[Pipeline #215](url) failed on feature/SSP-7-short-summary` with links to the failed jobs
+fn failure_comment(event: &PipelineEvent) -> String {
+    let attributes = &event.object_attributes;
+    let title = format!("{} failed on {}", link(&format!("Pipeline #{}", attributes.id), &event.web_url()), inline_code(&attributes.ref_));
+    let failed_jobs: Vec<String> = event.builds.iter()
+        .filter(|build| build.status == "failed")
+        .map(|build| link(&format!("{}: {}", build.stage, build.name), &event.job_url(build)))
+        .collect();
+    let comment = Markdown::new().raw(&title);
+    if failed_jobs.is_empty() {
+        comment.to_string()
+    } else {
+        comment.text("Failed jobs:").list(failed_jobs).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use gitlab_tools::events::samples;
+    use crate::pipelines::failure_comment;
+
+    #[test]
+    fn comment_of_failed_pipeline() {
+        assert_eq!(failure_comment(&samples::pipeline()), "[Pipeline \\#215](https://git.let4.me/serge/proj2/-/pipelines/215) failed on `feature/SSP-7-short-summary`\n\n\
+            Failed jobs:\n\n- [test: unit](https://git.let4.me/serge/proj2/-/jobs/1021)");
+    }
+}
@@ -0,0 +1,22 @@
+use gitlab_tools::async_client::AsyncGitlabClient;
+use youtrack_tools::rest_api::client::YoutrackClientImpl;
+use youtrack_tools::rest_api::service::admin::find_project;
+use youtrack_tools::rest_api::service::versions::{release_version, FIX_VERSIONS_FIELD};
+use git_utils::issue_keys::IssueKeyParser;
+
+/// Creates youtrack version named as the tag (`v1.4.0`), marks it released and adds it to
+/// "Fix versions" of every issue mentioned by commits since the previous tag.
+/// Returns ids of the stamped issues
+pub async fn release_tag(youtrack: &YoutrackClientImpl, gitlab: &AsyncGitlabClient, youtrack_project: &str, gitlab_project: &str, tag: &str)
+                         -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let commits = gitlab.commits_since_previous_tag(gitlab_project, tag).await?;
+    let messages: Vec<&str> = commits.iter().map(|commit| commit.message.as_str()).collect();
+    let issue_ids = IssueKeyParser::new([youtrack_project]).issue_ids(&messages);
+
+    let project = find_project(youtrack.http_client(), youtrack_project).await?
+        .ok_or_else(|| format!("youtrack project {} not found", youtrack_project))?;
+    let release_date = chrono::Utc::now().timestamp_millis() as u64;
+    release_version(youtrack.http_client(), &project.id, FIX_VERSIONS_FIELD, tag, release_date, &issue_ids).await?;
+    log::info!("Version {} released with {} issues of {}", tag, issue_ids.len(), youtrack_project);
+    Ok(issue_ids)
+}
@@ -0,0 +1,146 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+use serde::Deserialize;
+use youtrack_tools::rest_api::auth::{AuthProvider, HubClientCredentials, LazyToken, PermanentToken, TokenSource};
+use youtrack_tools::rest_api::base::proxy::ProxyConfig;
+use youtrack_tools::rest_api::client::Config;
+use gitlab_tools::config::GitlabConfig;
+
+/// Token is read from this variable when no other auth is configured
+const DEFAULT_TOKEN_ENV: &str = "YOUTRACK_TOKEN";
+
+const USER_AGENT: &str = concat!("youtrack-with-gitlab-integration/", env!("CARGO_PKG_VERSION"));
+
+/// `settings.yaml`, every value can be overridden by `APP_` environment variables, e.g. `APP_youtrack.url`.
+/// Plain tokens (`youtrack.token`, `gitlab.token`) are for local runs only, don't commit them
+#[derive(Deserialize, Debug)]
+pub struct Settings {
+    pub youtrack: YoutrackSettings,
+    pub gitlab: Option<GitlabConfig>,
+    pub webhook: Option<WebhookSettings>,
+}
+
+/// Receiver of gitlab hooks, `web-app serve`
+#[derive(Deserialize, Debug)]
+pub struct WebhookSettings {
+    pub listen: String,
+    /// Environment variable with the secret token of the hooks
+    pub secret_env: String,
+    /// File with the applied smart commits and their issues, they are kept in memory only when it's missing
+    pub processed_commits: Option<PathBuf>,
+    /// File with the youtrack comments of the mirrored merge request notes, kept in memory only when it's missing
+    pub mirrored_notes: Option<PathBuf>,
+}
+
+impl WebhookSettings {
+    /// An empty secret is rejected, otherwise hooks without the token would be accepted
+    pub fn secret(&self) -> Result<String, String> {
+        match std::env::var(&self.secret_env) {
+            Ok(secret) if !secret.is_empty() => Ok(secret),
+            Ok(_) => Err(format!("environment variable {} is empty", self.secret_env)),
+            Err(_) => Err(format!("environment variable {} is not set", self.secret_env)),
+        }
+    }
+}
+
+/// Only one way of auth is used, in order: `hub`, `token_file`, `token_env`, `token`
+#[derive(Deserialize, Debug)]
+pub struct YoutrackSettings {
+    pub url: String,
+    pub token: Option<String>,
+    pub token_file: Option<PathBuf>,
+    pub token_env: Option<String>,
+    pub hub: Option<HubSettings>,
+    /// `HTTPS_PROXY`/`NO_PROXY` are used when it's missing
+    pub proxy: Option<ProxySettings>,
+    /// Sent with every request
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ProxySettings {
+    pub url: String,
+    pub username: Option<String>,
+    /// Environment variable with the proxy password
+    pub password_env: Option<String>,
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct HubSettings {
+    pub url: String,
+    pub client_id: String,
+    /// Environment variable with the client secret
+    pub client_secret_env: String,
+    /// Id of the youtrack service in hub
+    pub scope: String,
+}
+
+impl Settings {
+    pub fn load() -> Result<Settings, config::ConfigError> {
+        let mut settings = config::Config::default();
+        settings
+            .merge(config::File::with_name("settings"))?
+            .merge(config::Environment::with_prefix("APP"))?;
+        settings.try_into()
+    }
+
+    pub fn webhook(&self) -> Result<&WebhookSettings, String> {
+        self.webhook.as_ref().ok_or_else(|| "webhook section is missing in settings.yaml".to_string())
+    }
+
+    pub fn gitlab(&self) -> Result<&GitlabConfig, String> {
+        self.gitlab.as_ref().ok_or_else(|| "gitlab section is missing in settings.yaml".to_string())
+    }
+}
+
+impl YoutrackSettings {
+    pub fn auth_provider(&self) -> Result<Arc<dyn AuthProvider>, String> {
+        if let Some(hub) = &self.hub {
+            let client_secret = std::env::var(&hub.client_secret_env)
+                .map_err(|_| format!("environment variable {} is not set", hub.client_secret_env))?;
+            let credentials = HubClientCredentials::new(hub.url.clone(), hub.client_id.clone(), client_secret, hub.scope.clone());
+            return Ok(Arc::new(credentials.with_proxy(self.proxy_config()?.as_ref())));
+        }
+        let provider: Arc<dyn AuthProvider> = match (&self.token_file, &self.token_env, &self.token) {
+            (Some(path), _, _) => Arc::new(LazyToken::new(TokenSource::File(path.clone()))),
+            (None, Some(name), _) => Arc::new(LazyToken::new(TokenSource::Env(name.clone()))),
+            (None, None, Some(token)) => Arc::new(PermanentToken::new(token.clone())),
+            (None, None, None) => Arc::new(LazyToken::new(TokenSource::Env(DEFAULT_TOKEN_ENV.to_string()))),
+        };
+        Ok(provider)
+    }
+
+    pub fn config(&self) -> Result<Config, String> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in &self.headers {
+            let name = HeaderName::from_bytes(name.as_bytes()).map_err(|error| format!("header {}: {}", name, error))?;
+            let value = HeaderValue::from_str(value).map_err(|error| format!("header {}: {}", name, error))?;
+            headers.insert(name, value);
+        }
+        Ok(Config::with_auth(self.url.clone(), self.auth_provider()?)
+            .with_proxy(self.proxy_config()?)
+            .with_user_agent(USER_AGENT)
+            .with_default_headers(headers))
+    }
+
+    fn proxy_config(&self) -> Result<Option<ProxyConfig>, String> {
+        let settings = match &self.proxy {
+            Some(settings) => settings,
+            None => return Ok(ProxyConfig::from_env()),
+        };
+        let mut proxy = ProxyConfig::new(&settings.url);
+        if let Some(username) = &settings.username {
+            proxy.username = Some(username.clone());
+        }
+        if let Some(name) = &settings.password_env {
+            proxy.password = Some(std::env::var(name).map_err(|_| format!("environment variable {} is not set", name))?);
+        }
+        proxy.no_proxy = settings.no_proxy.clone();
+        Ok(Some(proxy))
+    }
+}
@@ -0,0 +1,222 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use gitlab_tools::config::GitlabConfig;
+use gitlab_tools::events::{EventCommit, PushEvent};
+use git_utils::issue_keys::IssueKeyParser;
+use git_utils::smart_commits::{parse_smart_commit, Directive};
+use youtrack_tools::rest_api::client::YoutrackClientImpl;
+use youtrack_tools::rest_api::error::{YoutrackError, YoutrackResult};
+use youtrack_tools::rest_api::json_models::user::UserDto;
+use youtrack_tools::rest_api::markdown::{link, user_mention};
+use youtrack_tools::rest_api::service::commands::{command_value, execute_command, Command};
+use youtrack_tools::rest_api::service::comments::add_comment;
+use youtrack_tools::rest_api::service::issues::fetch_state_names;
+use youtrack_tools::rest_api::service::time_tracking::{add_work_item, AuthorRef, NewWorkItem};
+use youtrack_tools::rest_api::service::users::fetch_users;
+use crate::webhook::{EventHandler, HandlerResult};
+
+/// Users are fetched again after this time, new users are found by the next push
+const USERS_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Directives of commits with the issues they were applied to. The same commit comes again when its branch is merged
+/// or pushed to another branch, with a file they survive restarts of the service
+pub struct ProcessedCommits {
+    applied: Mutex<HashSet<String>>,
+    file: Option<PathBuf>,
+}
+
+impl ProcessedCommits {
+    pub fn in_memory() -> Self {
+        ProcessedCommits { applied: Mutex::new(HashSet::new()), file: None }
+    }
+
+    /// `<sha> <issue id> <directive index>` per line, the file is created by the first applied directive
+    pub fn load(file: PathBuf) -> std::io::Result<Self> {
+        let applied = match std::fs::read_to_string(&file) {
+            Ok(content) => content.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect(),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => HashSet::new(),
+            Err(error) => return Err(error),
+        };
+        Ok(ProcessedCommits { applied: Mutex::new(applied), file: Some(file) })
+    }
+
+    pub fn contains(&self, sha: &str, issue_id: &str, index: usize) -> bool {
+        self.applied.lock().unwrap().contains(&format!("{} {} {}", sha, issue_id, index))
+    }
+
+    /// Called after each applied directive, so a failed one and the rest are retried by the next push of the commit
+    /// without repeating the directives before it
+    pub fn mark(&self, sha: &str, issue_id: &str, index: usize) -> std::io::Result<()> {
+        let line = format!("{} {} {}", sha, issue_id, index);
+        let mut applied = self.applied.lock().unwrap();
+        if let Some(file) = &self.file {
+            let mut file = OpenOptions::new().create(true).append(true).open(file)?;
+            writeln!(file, "{}", line)?;
+        }
+        applied.insert(line);
+        Ok(())
+    }
+}
+
+/// Applies `SSP-7 #fixed #time 1h30m #comment reviewed with QA` of pushed commits to youtrack.
+/// `#word` is a transition only when it names a value of the State field of the issue, otherwise it is plain text.
+/// Work items are added on behalf of the youtrack user with the email of the commit author.
+/// The REST API has no way to run commands or add comments as another user, so they are made by the token user
+/// and signed with the author; work items of authors without a youtrack user are signed the same way
+pub struct SmartCommits {
+    youtrack: Arc<YoutrackClientImpl>,
+    config: GitlabConfig,
+    processed: ProcessedCommits,
+    users: Mutex<Option<(Instant, Arc<Vec<UserDto>>)>>,
+}
+
+impl SmartCommits {
+    pub fn new(youtrack: Arc<YoutrackClientImpl>, config: GitlabConfig, processed: ProcessedCommits) -> Self {
+        SmartCommits { youtrack, config, processed, users: Mutex::new(None) }
+    }
+
+    /// Users of youtrack to find authors by email, fetched once per `USERS_TTL`
+    async fn users(&self) -> YoutrackResult<Arc<Vec<UserDto>>> {
+        let cached = self.users.lock().unwrap().as_ref()
+            .filter(|(fetched_at, _)| fetched_at.elapsed() < USERS_TTL)
+            .map(|(_, users)| users.clone());
+        if let Some(users) = cached {
+            return Ok(users);
+        }
+        let users = Arc::new(fetch_users(self.youtrack.http_client()).await?);
+        *self.users.lock().unwrap() = Some((Instant::now(), users.clone()));
+        Ok(users)
+    }
+
+    /// State names of the issue for `#word` transitions, none when the issue has no State field
+    async fn state_names(&self, issue_id: &str) -> YoutrackResult<Vec<String>> {
+        match fetch_state_names(self.youtrack.http_client(), &issue_id.to_string()).await {
+            Err(YoutrackError::NoStateField(_)) => Ok(Vec::new()),
+            result => result,
+        }
+    }
+
+    async fn apply(&self, issue_id: &str, directive: &Directive, commit: &EventCommit, author: Option<&UserDto>) -> HandlerResult {
+        let client = self.youtrack.http_client();
+        let signature = signature(commit, author);
+        match directive {
+            Directive::Transition(state) => {
+                let mut command = Command::new(format!("State {}", command_value(state)), &[issue_id.to_string()]);
+                command.comment = Some(format!("State changed {}", signature));
+                execute_command(client, command).await?;
+            }
+            Directive::Comment(text) => {
+                add_comment(client, issue_id, &format!("{}\n\n{}", text, signature)).await?;
+            }
+            Directive::Time { minutes, text } => {
+                let mut item = NewWorkItem::new(*minutes);
+                let text = text.clone().unwrap_or_else(|| commit_title(commit).to_string());
+                item.text = Some(match author {
+                    Some(_) => text,
+                    None => format!("{}\n\n{}", text, signature),
+                });
+                item.date = commit.timestamp.as_deref()
+                    .and_then(|timestamp| chrono::DateTime::parse_from_rfc3339(timestamp).ok())
+                    .map(|date| date.timestamp_millis() as u64);
+                item.author = author.map(|user| AuthorRef { id: user.id.clone() });
+                add_work_item(client, issue_id, item).await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl EventHandler for SmartCommits {
+    async fn push(&self, event: &PushEvent) -> HandlerResult {
+        if event.is_removal() {
+            return Ok(());
+        }
+        if event.total_commits_count as usize > event.commits.len() {
+            log::warn!("Push to {} has {} commits, only the last {} are checked for smart commits",
+                       event.ref_, event.total_commits_count, event.commits.len());
+        }
+        let keys = IssueKeyParser::new(self.config.policies_of(&event.project.path_with_namespace).map(|policy| &policy.youtrack_project));
+        // every `#word` is a candidate here, the state names of the issues decide which of them are directives
+        let smart_commits: Vec<_> = event.commits.iter()
+            .filter_map(|commit| parse_smart_commit(&keys, &commit.message, |_| true).map(|smart_commit| (commit, smart_commit.issue_ids)))
+            .collect();
+        if smart_commits.is_empty() {
+            return Ok(());
+        }
+
+        let users = self.users().await?;
+        let mut state_names: HashMap<String, Vec<String>> = HashMap::new();
+        for (commit, issue_ids) in smart_commits {
+            let email = commit.author.as_ref().and_then(|author| author.email.as_deref());
+            let author = email.and_then(|email| users.iter().find(|user| matches!(&user.email, Some(user_email) if user_email.eq_ignore_ascii_case(email))));
+            for issue_id in &issue_ids {
+                if !state_names.contains_key(issue_id) {
+                    match self.state_names(issue_id).await {
+                        Ok(names) => state_names.insert(issue_id.clone(), names),
+                        Err(error) => {
+                            log::error!("Smart commit {} isn't applied to {}: {}", commit.id, issue_id, error);
+                            continue;
+                        }
+                    };
+                }
+                let names = &state_names[issue_id];
+                let directives = parse_smart_commit(&keys, &commit.message, |word| names.iter().any(|name| name.eq_ignore_ascii_case(word)))
+                    .map(|smart_commit| smart_commit.directives)
+                    .unwrap_or_default();
+                for (index, directive) in directives.iter().enumerate() {
+                    if self.processed.contains(&commit.id, issue_id, index) {
+                        continue;
+                    }
+                    if let Err(error) = self.apply(issue_id, directive, commit, author).await {
+                        log::error!("Directive {} of smart commit {} isn't applied to {}: {}", index + 1, commit.id, issue_id, error);
+                        break;
+                    }
+                    self.processed.mark(&commit.id, issue_id, index)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn commit_title(commit: &EventCommit) -> &str {
+    commit.title.as_deref().unwrap_or_else(|| commit.message.lines().next().unwrap_or_default())
+}
+
+/// `by @serge in [7e3a0c6f](url)`, the author name is used when there is no youtrack user with the email
+fn signature(commit: &EventCommit, author: Option<&UserDto>) -> String {
+    let short_sha = &commit.id[..commit.id.len().min(8)];
+    let author = match author.and_then(|user| user.login.as_deref()) {
+        Some(login) => user_mention(login),
+        None => commit.author.as_ref().map(|author| author.name.clone()).unwrap_or_else(|| "unknown author".to_string()),
+    };
+    format!("by {} in {}", author, link(short_sha, &commit.url))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::smart_commits::ProcessedCommits;
+
+    #[test]
+    fn commits_are_processed_once() {
+        let file = std::env::temp_dir().join(format!("processed-commits-{}.txt", std::process::id()));
+        let _ = std::fs::remove_file(&file);
+        let processed = ProcessedCommits::load(file.clone()).unwrap();
+        assert!(!processed.contains("7e3a0c6f", "SSP-7", 0));
+        processed.mark("7e3a0c6f", "SSP-7", 0).unwrap();
+        assert!(processed.contains("7e3a0c6f", "SSP-7", 0));
+
+        let reloaded = ProcessedCommits::load(file.clone()).unwrap();
+        assert!(reloaded.contains("7e3a0c6f", "SSP-7", 0));
+        assert!(!reloaded.contains("7e3a0c6f", "SSP-7", 1));
+        assert!(!reloaded.contains("7e3a0c6f", "SSP-8", 0));
+        assert!(!reloaded.contains("2b4d1c5b", "SSP-7", 0));
+        std::fs::remove_file(&file).unwrap();
+    }
+}
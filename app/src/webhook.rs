@@ -0,0 +1,311 @@
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use async_trait::async_trait;
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use hyper::service::{make_service_fn, service_fn};
+use listenfd::ListenFd;
+use serde::Deserialize;
+use gitlab_tools::events::{DeploymentEvent, GitlabEvent, MergeRequestEvent, NoteEvent, PipelineEvent, PushEvent};
+
+/// Path of the project and system hooks
+pub const WEBHOOK_PATH: &str = "/gitlab/webhook";
+const TOKEN_HEADER: &str = "X-Gitlab-Token";
+/// Path of the state changes posted by the youtrack workflow, see `workflow/index.js`
+pub const STATE_CHANGE_PATH: &str = "/youtrack/state";
+/// The workflow sends the same secret as the gitlab hooks
+const STATE_TOKEN_HEADER: &str = "X-Youtrack-Token";
+
+pub type HandlerResult = Result<(), Box<dyn std::error::Error + Send + Sync>>;
+
+/// State of a youtrack issue changed, `old_state` is missing for new issues
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StateChange {
+    pub issue: String,
+    pub state: String,
+    pub old_state: Option<String>,
+}
+
+/// Reaction to gitlab events, events which aren't interesting are ignored by the default methods
+#[async_trait]
+pub trait EventHandler: Send + Sync {
+    async fn merge_request(&self, _event: &MergeRequestEvent) -> HandlerResult {
+        Ok(())
+    }
+
+    async fn push(&self, _event: &PushEvent) -> HandlerResult {
+        Ok(())
+    }
+
+    async fn tag_push(&self, _event: &PushEvent) -> HandlerResult {
+        Ok(())
+    }
+
+    async fn pipeline(&self, _event: &PipelineEvent) -> HandlerResult {
+        Ok(())
+    }
+
+    async fn note(&self, _event: &NoteEvent) -> HandlerResult {
+        Ok(())
+    }
+
+    async fn deployment(&self, _event: &DeploymentEvent) -> HandlerResult {
+        Ok(())
+    }
+
+    async fn state_change(&self, _change: &StateChange) -> HandlerResult {
+        Ok(())
+    }
+}
+
+/// Writes every received event to the log
+pub struct LogHandler;
+
+#[async_trait]
+impl EventHandler for LogHandler {
+    async fn merge_request(&self, event: &MergeRequestEvent) -> HandlerResult {
+        log::info!("Merge request !{} of {}: {:?}", event.object_attributes.iid, event.project.path_with_namespace, event.object_attributes.action);
+        Ok(())
+    }
+
+    async fn push(&self, event: &PushEvent) -> HandlerResult {
+        log::info!("Push of {} commits to {} of {}", event.total_commits_count, event.ref_, event.project.path_with_namespace);
+        Ok(())
+    }
+
+    async fn tag_push(&self, event: &PushEvent) -> HandlerResult {
+        log::info!("Tag {} of {}", event.ref_, event.project.path_with_namespace);
+        Ok(())
+    }
+
+    async fn pipeline(&self, event: &PipelineEvent) -> HandlerResult {
+        log::info!("Pipeline {} of {}: {}", event.object_attributes.id, event.project.path_with_namespace, event.object_attributes.status);
+        Ok(())
+    }
+
+    async fn note(&self, event: &NoteEvent) -> HandlerResult {
+        log::info!("Note {} on {} of {}", event.object_attributes.id, event.object_attributes.noteable_type, event.project.path_with_namespace);
+        Ok(())
+    }
+
+    async fn deployment(&self, event: &DeploymentEvent) -> HandlerResult {
+        log::info!("Deployment of {} to {}: {}", event.short_sha, event.environment, event.status);
+        Ok(())
+    }
+
+    async fn state_change(&self, change: &StateChange) -> HandlerResult {
+        log::info!("{} moved from {} to {}", change.issue, change.old_state.as_deref().unwrap_or("nothing"), change.state);
+        Ok(())
+    }
+}
+
+/// Receiver of gitlab hooks and of the state changes of the youtrack workflow. Requests are answered
+/// as soon as the event is parsed, handlers run in background because gitlab doesn't wait for slow hooks
+pub struct Webhook {
+    secret: String,
+    handlers: Vec<Arc<dyn EventHandler>>,
+}
+
+impl Webhook {
+    /// `secret` is the "Secret token" of the hook, sent back in `X-Gitlab-Token`
+    pub fn new(secret: String) -> Self {
+        Webhook { secret, handlers: Vec::new() }
+    }
+
+    pub fn with_handler(mut self, handler: Arc<dyn EventHandler>) -> Self {
+        self.handlers.push(handler);
+        self
+    }
+
+    pub async fn handle(self: Arc<Self>, request: Request<Body>) -> Response<Body> {
+        let token_header = match request.uri().path() {
+            WEBHOOK_PATH => TOKEN_HEADER,
+            STATE_CHANGE_PATH => STATE_TOKEN_HEADER,
+            _ => return status(StatusCode::NOT_FOUND),
+        };
+        if request.method() != Method::POST {
+            return status(StatusCode::METHOD_NOT_ALLOWED);
+        }
+        let token = request.headers().get(token_header).map(|token| token.as_bytes()).unwrap_or_default();
+        if !constant_time_eq(token, self.secret.as_bytes()) {
+            log::warn!("Hook with a wrong {} is rejected", token_header);
+            return status(StatusCode::UNAUTHORIZED);
+        }
+
+        let path = request.uri().path().to_string();
+        let body = match hyper::body::to_bytes(request.into_body()).await {
+            Ok(body) => body,
+            Err(error) => {
+                log::warn!("Hook body isn't read: {}", error);
+                return status(StatusCode::BAD_REQUEST);
+            }
+        };
+        if path == STATE_CHANGE_PATH {
+            return match serde_json::from_slice::<StateChange>(&body) {
+                Ok(change) => {
+                    tokio::spawn(async move { self.dispatch_state_change(&change).await });
+                    status(StatusCode::OK)
+                }
+                Err(error) => {
+                    log::warn!("State change is rejected: {}", error);
+                    status(StatusCode::BAD_REQUEST)
+                }
+            };
+        }
+        let event = match GitlabEvent::from_slice(&body) {
+            Ok(event) => event,
+            Err(error) => {
+                log::warn!("Gitlab hook is rejected: {}", error);
+                return status(StatusCode::BAD_REQUEST);
+            }
+        };
+
+        tokio::spawn(async move { self.dispatch(&event).await });
+        status(StatusCode::OK)
+    }
+
+    /// Runs handlers one by one, an error of one handler doesn't stop the others
+    pub async fn dispatch(&self, event: &GitlabEvent) {
+        for handler in &self.handlers {
+            let result = match event {
+                GitlabEvent::MergeRequest(event) => handler.merge_request(event).await,
+                GitlabEvent::Push(event) => handler.push(event).await,
+                GitlabEvent::TagPush(event) => handler.tag_push(event).await,
+                GitlabEvent::Pipeline(event) => handler.pipeline(event).await,
+                GitlabEvent::Note(event) => handler.note(event).await,
+                GitlabEvent::Deployment(event) => handler.deployment(event).await,
+                GitlabEvent::Other(kind) => {
+                    log::debug!("Gitlab event {} is ignored", kind);
+                    return;
+                }
+            };
+            if let Err(error) = result {
+                log::error!("Gitlab {} event isn't handled: {}", event.kind(), error);
+            }
+        }
+    }
+
+    pub async fn dispatch_state_change(&self, change: &StateChange) {
+        for handler in &self.handlers {
+            if let Err(error) = handler.state_change(change).await {
+                log::error!("State change of {} isn't handled: {}", change.issue, error);
+            }
+        }
+    }
+}
+
+/// Listens on the socket passed by `systemfd` (`cargo rd`) or binds `listen`
+pub async fn serve(webhook: Webhook, listen: SocketAddr) -> Result<(), Box<dyn std::error::Error>> {
+    let webhook = Arc::new(webhook);
+    let make_service = make_service_fn(move |_connection| {
+        let webhook = webhook.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let webhook = webhook.clone();
+                async move { Ok::<_, Infallible>(webhook.handle(request).await) }
+            }))
+        }
+    });
+
+    let server = match ListenFd::from_env().take_tcp_listener(0)? {
+        Some(listener) => Server::from_tcp(listener)?,
+        None => Server::try_bind(&listen)?,
+    };
+    log::info!("Gitlab hooks are received on {}, youtrack state changes on {}", WEBHOOK_PATH, STATE_CHANGE_PATH);
+    server.serve(make_service).await?;
+    Ok(())
+}
+
+fn status(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::from(status.canonical_reason().unwrap_or_default()));
+    *response.status_mut() = status;
+    response
+}
+
+/// Comparison time doesn't depend on the position of the first wrong byte
+fn constant_time_eq(left: &[u8], right: &[u8]) -> bool {
+    left.len() == right.len() && left.iter().zip(right).fold(0, |difference, (l, r)| difference | (l ^ r)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use async_trait::async_trait;
+    use hyper::{Body, Request, StatusCode};
+    use gitlab_tools::events::{samples, MergeRequestEvent, PushEvent};
+    use crate::webhook::{EventHandler, HandlerResult, StateChange, Webhook, STATE_CHANGE_PATH, WEBHOOK_PATH};
+
+    #[derive(Default)]
+    struct Recorder {
+        events: Mutex<Vec<String>>,
+    }
+
+    #[async_trait]
+    impl EventHandler for Recorder {
+        async fn merge_request(&self, event: &MergeRequestEvent) -> HandlerResult {
+            self.events.lock().unwrap().push(format!("merge_request !{}", event.object_attributes.iid));
+            Ok(())
+        }
+
+        async fn push(&self, event: &PushEvent) -> HandlerResult {
+            self.events.lock().unwrap().push(format!("push {}", event.ref_));
+            Err("youtrack is down".into())
+        }
+
+        async fn state_change(&self, change: &StateChange) -> HandlerResult {
+            self.events.lock().unwrap().push(format!("{} {}", change.issue, change.state));
+            Ok(())
+        }
+    }
+
+    fn request(token: &str, body: &'static [u8]) -> Request<Body> {
+        Request::post(WEBHOOK_PATH).header("X-Gitlab-Token", token).body(Body::from(body)).unwrap()
+    }
+
+    #[test]
+    fn token_is_verified() {
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let webhook = Arc::new(Webhook::new("secret".to_string()));
+        runtime.block_on(async {
+            assert_eq!(webhook.clone().handle(request("wrong", samples::payload("push"))).await.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(webhook.clone().handle(request("secret", b"{}")).await.status(), StatusCode::OK);
+            assert_eq!(webhook.clone().handle(request("secret", b"not json")).await.status(), StatusCode::BAD_REQUEST);
+            assert_eq!(webhook.clone().handle(request("secret", samples::payload("push"))).await.status(), StatusCode::OK);
+        });
+    }
+
+    #[test]
+    fn state_changes_are_received() {
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let recorder = Arc::new(Recorder::default());
+        let webhook = Arc::new(Webhook::new("secret".to_string()).with_handler(recorder.clone()));
+        let change = |token: &str, body: &'static str| {
+            Request::post(STATE_CHANGE_PATH).header("X-Youtrack-Token", token).body(Body::from(body)).unwrap()
+        };
+        let payload = r#"{"issue": "SSP-7", "state": "In Progress", "oldState": "Open"}"#;
+        runtime.block_on(async {
+            assert_eq!(webhook.clone().handle(change("wrong", payload)).await.status(), StatusCode::UNAUTHORIZED);
+            assert_eq!(webhook.clone().handle(request("secret", payload.as_bytes())).await.status(), StatusCode::OK);
+            assert_eq!(webhook.clone().handle(change("secret", "{}")).await.status(), StatusCode::BAD_REQUEST);
+            assert_eq!(webhook.clone().handle(change("secret", payload)).await.status(), StatusCode::OK);
+            tokio::time::delay_for(std::time::Duration::from_millis(100)).await;
+        });
+        assert_eq!(*recorder.events.lock().unwrap(), vec!["SSP-7 In Progress".to_string()]);
+    }
+
+    #[test]
+    fn events_are_dispatched_to_all_handlers() {
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let first = Arc::new(Recorder::default());
+        let second = Arc::new(Recorder::default());
+        let webhook = Webhook::new("secret".to_string()).with_handler(first.clone()).with_handler(second.clone());
+        runtime.block_on(async {
+            webhook.dispatch(&samples::sample_event("push")).await;
+            webhook.dispatch(&samples::sample_event("merge_request")).await;
+        });
+        let expected = vec!["push refs/heads/feature/SSP-7-short-summary".to_string(), "merge_request !12".to_string()];
+        assert_eq!(*first.events.lock().unwrap(), expected);
+        assert_eq!(*second.events.lock().unwrap(), expected);
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+use gitlab::AsyncGitlab;
+use gitlab::api::{self, AsyncQuery};
+use tokio::runtime::Runtime;
+use crate::branch::{branch_endpoint, create_branch_endpoint, found, protect_branch_endpoint, Branch};
+use crate::client::{builder, first_user_id, merge_request_commits_endpoint, merge_request_endpoint, user_endpoint, MergeRequestParams};
+use crate::config::GitlabConfig;
+use crate::error::{GitlabResult, GitlabToolsError};
+use crate::merge_request::MergeRequest;
+use crate::release::{commits_endpoint, compare_endpoint, previous_of, tags_endpoint, Commit, Comparison, Tag};
+
+/// Requests of the client are driven by this many threads
+const RUNTIME_THREADS: usize = 2;
+
+/// Async counterpart of `GitlabClient` with the same operations, endpoints and responses are shared with it.
+///
+/// The client owns a tokio 1 runtime: `AsyncGitlab` sends requests with reqwest, which needs the reactor
+/// of tokio 1, while `web-app` runs on tokio 0.2 (hyper 0.13). Awaiting reqwest
+/// on the 0.2 runtime panics with "there is no reactor running", so requests are spawned on the runtime
+/// of the client and the caller only awaits the join handle, which works on any executor.
+/// The runtime can go away once the app moves to tokio 1
+#[derive(Clone)]
+pub struct AsyncGitlabClient {
+    inner: AsyncGitlab,
+    runtime: Arc<Runtime>,
+}
+
+impl AsyncGitlabClient {
+    pub async fn new(config: &GitlabConfig) -> GitlabResult<Self> {
+        let builder = builder(config)?;
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(RUNTIME_THREADS)
+            .thread_name("gitlab-tools")
+            .enable_all()
+            .build()
+            .map_err(|error| GitlabToolsError::Runtime(error.to_string()))?;
+        let runtime = Arc::new(runtime);
+        let inner = run(&runtime, async move { Ok(builder.build_async().await?) }).await?;
+        Ok(AsyncGitlabClient { inner, runtime })
+    }
+
+    /// Underlying client for endpoints which aren't wrapped, queries of it must run on `spawn`
+    pub fn api(&self) -> &AsyncGitlab {
+        &self.inner
+    }
+
+    /// Runs a request of `api()` on the runtime of the client
+    pub async fn spawn<T, F>(&self, request: F) -> GitlabResult<T>
+        where F: Future<Output=GitlabResult<T>> + Send + 'static, T: Send + 'static {
+        run(&self.runtime, request).await
+    }
+
+    /// Runs the request built by `request` with the underlying client on the runtime of the client.
+    /// Endpoints borrow their parameters, so `request` gets owned copies of them
+    async fn query<T, F>(&self, request: impl FnOnce(AsyncGitlab) -> F) -> GitlabResult<T>
+        where F: Future<Output=GitlabResult<T>> + Send + 'static, T: Send + 'static {
+        self.spawn(request(self.inner.clone())).await
+    }
+
+    pub async fn create_merge_request(&self, params: &MergeRequestParams) -> GitlabResult<MergeRequest> {
+        let mut params = params.clone();
+        if let (None, Some(username)) = (params.assignee_id, &params.assignee_username) {
+            params.assignee_id = self.find_user_id(username).await?;
+        }
+        self.query(|client| async move { Ok(merge_request_endpoint(&params)?.query_async(&client).await?) }).await
+    }
+
+    pub async fn find_user_id(&self, username: &str) -> GitlabResult<Option<u64>> {
+        let username = username.to_string();
+        self.query(|client| async move { Ok(first_user_id(user_endpoint(&username)?.query_async(&client).await?)) }).await
+    }
+
+    /// All commits of the merge request, they are still known after the source branch is removed
+    pub async fn merge_request_commits(&self, project: &str, iid: u64) -> GitlabResult<Vec<Commit>> {
+        let project = project.to_string();
+        self.query(|client| async move {
+            Ok(api::paged(merge_request_commits_endpoint(&project, iid)?, api::Pagination::All).query_async(&client).await?)
+        }).await
+    }
+
+    /// `None` when the project has no such branch
+    pub async fn branch(&self, project: &str, name: &str) -> GitlabResult<Option<Branch>> {
+        let (project, name) = (project.to_string(), name.to_string());
+        self.query(|client| async move { found(branch_endpoint(&project, &name)?.query_async(&client).await) }).await
+    }
+
+    pub async fn branch_exists(&self, project: &str, name: &str) -> GitlabResult<bool> {
+        Ok(self.branch(project, name).await?.is_some())
+    }
+
+    /// `from` is a branch, tag or commit sha
+    pub async fn create_branch(&self, project: &str, name: &str, from: &str) -> GitlabResult<Branch> {
+        let (project, name, from) = (project.to_string(), name.to_string(), from.to_string());
+        self.query(|client| async move { Ok(create_branch_endpoint(&project, &name, &from)?.query_async(&client).await?) }).await
+    }
+
+    /// Protects with the default access levels of gitlab: push and merge by maintainers
+    pub async fn protect_branch(&self, project: &str, name: &str) -> GitlabResult<()> {
+        let (project, name) = (project.to_string(), name.to_string());
+        self.query(|client| async move {
+            api::ignore(protect_branch_endpoint(&project, &name)?).query_async(&client).await?;
+            Ok(())
+        }).await
+    }
+
+    /// Tag released before `tag`, tags are compared in version order (`v1.3.2` < `v1.4.0`)
+    pub async fn previous_tag(&self, project: &str, tag: &str) -> GitlabResult<Option<Tag>> {
+        let (project, tag) = (project.to_string(), tag.to_string());
+        self.query(|client| async move {
+            let tags = api::paged(tags_endpoint(&project)?, api::Pagination::All).query_async(&client).await?;
+            Ok(previous_of(tags, &tag))
+        }).await
+    }
+
+    /// Commits which are in `tag` but not in the previous tag, all commits of the tag for the first release
+    pub async fn commits_since_previous_tag(&self, project: &str, tag: &str) -> GitlabResult<Vec<Commit>> {
+        let previous = self.previous_tag(project, tag).await?;
+        let (project, tag) = (project.to_string(), tag.to_string());
+        self.query(|client| async move {
+            match previous {
+                Some(previous) => {
+                    let comparison: Comparison = compare_endpoint(&project, previous.name, &tag)?.query_async(&client).await?;
+                    Ok(comparison.commits)
+                }
+                None => Ok(api::paged(commits_endpoint(&project, &tag)?, api::Pagination::All).query_async(&client).await?),
+            }
+        }).await
+    }
+}
+
+impl fmt::Debug for AsyncGitlabClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncGitlabClient").finish()
+    }
+}
+
+async fn run<T, F>(runtime: &Runtime, request: F) -> GitlabResult<T>
+    where F: Future<Output=GitlabResult<T>> + Send + 'static, T: Send + 'static {
+    runtime.spawn(request).await.map_err(|error| GitlabToolsError::Runtime(error.to_string()))?
+}
@@ -0,0 +1,62 @@
+use gitlab::api::{self, ApiError, Query};
+use gitlab::api::projects::repository::branches::{Branch as BranchEndpoint, CreateBranch};
+use gitlab::api::projects::protected_branches::ProtectBranch;
+use serde::Deserialize;
+use crate::client::GitlabClient;
+use crate::error::{endpoint_error, GitlabResult, GitlabToolsError};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Branch {
+    pub name: String,
+    pub protected: bool,
+    pub web_url: Option<String>,
+    pub commit: BranchCommit,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BranchCommit {
+    pub id: String,
+}
+
+impl GitlabClient {
+    /// `None` when the project has no such branch
+    pub fn branch(&self, project: &str, name: &str) -> GitlabResult<Option<Branch>> {
+        found(branch_endpoint(project, name)?.query(self.api()))
+    }
+
+    pub fn branch_exists(&self, project: &str, name: &str) -> GitlabResult<bool> {
+        Ok(self.branch(project, name)?.is_some())
+    }
+
+    /// `from` is a branch, tag or commit sha
+    pub fn create_branch(&self, project: &str, name: &str, from: &str) -> GitlabResult<Branch> {
+        Ok(create_branch_endpoint(project, name, from)?.query(self.api())?)
+    }
+
+    /// Protects with the default access levels of gitlab: push and merge by maintainers
+    pub fn protect_branch(&self, project: &str, name: &str) -> GitlabResult<()> {
+        api::ignore(protect_branch_endpoint(project, name)?).query(self.api())?;
+        Ok(())
+    }
+}
+
+pub(crate) fn branch_endpoint<'a>(project: &'a str, name: &'a str) -> GitlabResult<BranchEndpoint<'a>> {
+    BranchEndpoint::builder().project(project).branch(name).build().map_err(endpoint_error)
+}
+
+pub(crate) fn create_branch_endpoint<'a>(project: &'a str, name: &'a str, from: &'a str) -> GitlabResult<CreateBranch<'a>> {
+    CreateBranch::builder().project(project).branch(name).ref_(from).build().map_err(endpoint_error)
+}
+
+pub(crate) fn protect_branch_endpoint<'a>(project: &'a str, name: &'a str) -> GitlabResult<ProtectBranch<'a>> {
+    ProtectBranch::builder().project(project).name(name).build().map_err(endpoint_error)
+}
+
+/// Response of `branch_endpoint`, 404 means there is no such branch
+pub(crate) fn found(response: Result<Branch, ApiError<gitlab::RestError>>) -> GitlabResult<Option<Branch>> {
+    match response.map_err(GitlabToolsError::from) {
+        Ok(branch) => Ok(Some(branch)),
+        Err(error) if error.is_not_found() => Ok(None),
+        Err(error) => Err(error),
+    }
+}
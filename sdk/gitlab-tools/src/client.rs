@@ -0,0 +1,153 @@
+use std::fmt;
+use gitlab::{Gitlab, GitlabBuilder};
+use gitlab::api::{self, Query};
+use gitlab::api::projects::merge_requests::{CreateMergeRequest, MergeRequestCommits};
+use gitlab::api::users::Users;
+use serde::Deserialize;
+use crate::config::{GitlabConfig, TokenType};
+use crate::error::{endpoint_error, GitlabResult};
+use crate::merge_request::MergeRequest;
+use crate::release::Commit;
+
+/// Identifies the integration in gitlab logs
+const USER_AGENT: &str = concat!("gitlab-tools/", env!("CARGO_PKG_VERSION"));
+
+/// Parameters of a new merge request, `project` is a path like `serge/proj2` or an id
+#[derive(Debug, Clone)]
+pub struct MergeRequestParams {
+    pub project: String,
+    pub title: String,
+    pub description: String,
+    pub source_branch: String,
+    pub target_branch: String,
+    pub remove_source_branch: bool,
+    pub squash: bool,
+    pub labels: Vec<String>,
+    pub assignee_id: Option<u64>,
+    /// Resolved to `assignee_id` on creation, the merge request is unassigned when gitlab has no such user
+    pub assignee_username: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct UserId {
+    pub(crate) id: u64,
+}
+
+impl MergeRequestParams {
+    pub fn new(project: &str, title: &str, source_branch: &str, target_branch: &str) -> Self {
+        MergeRequestParams {
+            project: project.to_string(),
+            title: title.to_string(),
+            description: String::new(),
+            source_branch: source_branch.to_string(),
+            target_branch: target_branch.to_string(),
+            remove_source_branch: true,
+            squash: true,
+            labels: Vec::new(),
+            assignee_id: None,
+            assignee_username: None,
+        }
+    }
+}
+
+/// Sync gitlab client built from `GitlabConfig`, requests block the current thread
+#[derive(Clone)]
+pub struct GitlabClient {
+    inner: Gitlab,
+}
+
+impl GitlabClient {
+    pub fn new(config: &GitlabConfig) -> GitlabResult<Self> {
+        let inner = builder(config)?.build()?;
+        Ok(GitlabClient { inner })
+    }
+
+    /// Underlying client for endpoints which aren't wrapped, e.g. `release` functions
+    pub fn api(&self) -> &Gitlab {
+        &self.inner
+    }
+
+    pub fn prepare_merge_request<'a>(&self, params: &'a MergeRequestParams) -> GitlabResult<CreateMergeRequest<'a>> {
+        merge_request_endpoint(params)
+    }
+
+    pub fn create_merge_request(&self, params: &MergeRequestParams) -> GitlabResult<MergeRequest> {
+        let mut params = params.clone();
+        if let (None, Some(username)) = (params.assignee_id, &params.assignee_username) {
+            params.assignee_id = self.find_user_id(username)?;
+        }
+        let endpoint = self.prepare_merge_request(&params)?;
+        Ok(endpoint.query(&self.inner)?)
+    }
+
+    /// All commits of the merge request, they are still known after the source branch is removed
+    pub fn merge_request_commits(&self, project: &str, iid: u64) -> GitlabResult<Vec<Commit>> {
+        Ok(api::paged(merge_request_commits_endpoint(project, iid)?, api::Pagination::All).query(&self.inner)?)
+    }
+
+    pub fn find_user_id(&self, username: &str) -> GitlabResult<Option<u64>> {
+        Ok(first_user_id(user_endpoint(username)?.query(&self.inner)?))
+    }
+}
+
+impl fmt::Debug for GitlabClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GitlabClient").finish()
+    }
+}
+
+pub(crate) fn merge_request_endpoint(params: &MergeRequestParams) -> GitlabResult<CreateMergeRequest<'_>> {
+    let mut builder = CreateMergeRequest::builder();
+    builder
+        .project(params.project.as_str())
+        .title(params.title.as_str())
+        .description(params.description.as_str())
+        .remove_source_branch(params.remove_source_branch)
+        .squash(params.squash)
+        .source_branch(params.source_branch.as_str())
+        .target_branch(params.target_branch.as_str());
+    if !params.labels.is_empty() {
+        builder.labels(params.labels.iter().map(String::as_str));
+    }
+    if let Some(assignee_id) = params.assignee_id {
+        builder.assignee(assignee_id);
+    }
+    builder.build().map_err(endpoint_error)
+}
+
+pub(crate) fn merge_request_commits_endpoint(project: &str, iid: u64) -> GitlabResult<MergeRequestCommits<'_>> {
+    MergeRequestCommits::builder()
+        .project(project)
+        .merge_request(iid)
+        .build()
+        .map_err(endpoint_error)
+}
+
+pub(crate) fn user_endpoint(username: &str) -> GitlabResult<Users<'_>> {
+    Users::builder().username(username).build().map_err(endpoint_error)
+}
+
+/// Response of `user_endpoint`, usernames are unique so there is one user at most
+pub(crate) fn first_user_id(users: Vec<UserId>) -> Option<u64> {
+    users.first().map(|user| user.id)
+}
+
+pub(crate) fn builder(config: &GitlabConfig) -> GitlabResult<GitlabBuilder> {
+    let (host, https) = config.host();
+    let token = config.resolve_token()?;
+    let mut builder = match config.token_type {
+        TokenType::Job => GitlabBuilder::new_with_job_token(host, token),
+        TokenType::Personal | TokenType::OAuth => GitlabBuilder::new(host, token),
+    };
+    if config.token_type == TokenType::OAuth {
+        builder.oauth2_token();
+    }
+    if !https {
+        builder.insecure();
+    }
+    if config.insecure {
+        builder.cert_insecure();
+    }
+    builder.user_agent(USER_AGENT);
+    Ok(builder)
+}
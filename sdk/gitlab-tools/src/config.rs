@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use serde::Deserialize;
+use crate::error::{GitlabResult, GitlabToolsError};
+
+/// Token is read from this variable when neither `token` nor `token_env` is set
+const DEFAULT_TOKEN_ENV: &str = "GITLAB_TOKEN";
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenType {
+    /// Personal, project or group access token
+    #[default]
+    Personal,
+    OAuth,
+    /// `CI_JOB_TOKEN` of a pipeline
+    Job,
+}
+
+/// `gitlab:` section of `settings.yaml`
+#[derive(Deserialize, Debug, Clone)]
+pub struct GitlabConfig {
+    /// `https://git.let4.me`, `http://` switches the client to plain http
+    pub url: String,
+    #[serde(default)]
+    pub token_type: TokenType,
+    pub token: Option<String>,
+    pub token_env: Option<String>,
+    /// Accepts self-signed certificates
+    #[serde(default)]
+    pub insecure: bool,
+    #[serde(default)]
+    pub projects: Vec<ProjectPolicy>,
+}
+
+/// How issues of a youtrack project are turned into branches and merge requests of a gitlab project
+#[derive(Deserialize, Debug, Clone)]
+pub struct ProjectPolicy {
+    /// Short name, e.g. `SSP`
+    pub youtrack_project: String,
+    /// Path like `serge/proj2`
+    pub gitlab_project: String,
+    #[serde(default = "default_target_branch")]
+    pub target_branch: String,
+    #[serde(default = "default_branch_prefix")]
+    pub branch_prefix: String,
+    /// String field of the youtrack project where the branch name of an issue is written
+    #[serde(default = "default_branch_field")]
+    pub branch_field: String,
+    /// New branches are protected, only maintainers can push to them
+    #[serde(default)]
+    pub protect_branches: bool,
+    /// Youtrack login to gitlab username, the same username is used for missing logins
+    #[serde(default)]
+    pub assignees: HashMap<String, String>,
+    /// State of a mentioned issue to its state after the merge, issues in other states only get the comment
+    #[serde(default = "default_merge_transitions")]
+    pub merge_transitions: HashMap<String, String>,
+    /// Field where `passed` or `failed` of the last finished pipeline of the issue branch is written
+    #[serde(default)]
+    pub build_field: Option<String>,
+    /// Tags `build-passed` and `build-failed` follow the pipelines of the issue branch
+    #[serde(default)]
+    pub build_tags: bool,
+    /// State a merged issue is moved to when a pipeline of the default branch fails, e.g. `Reopened`.
+    /// Merged issues are the ones in a target state of `merge_transitions`, they aren't reopened when it's missing
+    #[serde(default)]
+    pub reopen_state: Option<String>,
+}
+
+impl ProjectPolicy {
+    pub fn new(youtrack_project: &str, gitlab_project: &str) -> Self {
+        ProjectPolicy {
+            youtrack_project: youtrack_project.to_string(),
+            gitlab_project: gitlab_project.to_string(),
+            target_branch: default_target_branch(),
+            branch_prefix: default_branch_prefix(),
+            branch_field: default_branch_field(),
+            protect_branches: false,
+            assignees: HashMap::new(),
+            merge_transitions: default_merge_transitions(),
+            build_field: None,
+            build_tags: false,
+            reopen_state: None,
+        }
+    }
+
+    /// State an issue in `state` is moved to when its merge request is merged
+    pub fn merged_state(&self, state: &str) -> Option<&str> {
+        self.merge_transitions.iter()
+            .find(|(from, _)| from.eq_ignore_ascii_case(state))
+            .map(|(_, to)| to.as_str())
+    }
+
+    /// The state is one of the targets of `merge_transitions`, e.g. `Fixed`
+    pub fn is_merged_state(&self, state: &str) -> bool {
+        self.merge_transitions.values().any(|merged| merged.eq_ignore_ascii_case(state))
+    }
+
+    pub fn gitlab_username<'a>(&'a self, youtrack_login: &'a str) -> &'a str {
+        self.assignees.get(youtrack_login).map(String::as_str).unwrap_or(youtrack_login)
+    }
+}
+
+fn default_target_branch() -> String {
+    "master".to_string()
+}
+
+fn default_branch_prefix() -> String {
+    "feature/".to_string()
+}
+
+fn default_branch_field() -> String {
+    "Branch".to_string()
+}
+
+fn default_merge_transitions() -> HashMap<String, String> {
+    ["In Progress", "Wait for merge"].iter().map(|state| (state.to_string(), "Fixed".to_string())).collect()
+}
+
+impl GitlabConfig {
+    pub fn new(url: &str, token: &str) -> Self {
+        GitlabConfig { url: url.to_string(), token_type: TokenType::Personal, token: Some(token.to_string()), token_env: None, insecure: false, projects: Vec::new() }
+    }
+
+    /// Policy of the youtrack project, `SSP-7` and `SSP` give the same policy
+    pub fn policy(&self, youtrack_project_or_issue: &str) -> Option<&ProjectPolicy> {
+        let short_name = youtrack_project_or_issue.split('-').next().unwrap_or_default();
+        self.projects.iter().find(|policy| policy.youtrack_project.eq_ignore_ascii_case(short_name))
+    }
+
+    /// Policies of the youtrack projects which live in the gitlab project
+    pub fn policies_of(&self, gitlab_project: &str) -> impl Iterator<Item=&ProjectPolicy> {
+        let gitlab_project = gitlab_project.to_string();
+        self.projects.iter().filter(move |policy| policy.gitlab_project.eq_ignore_ascii_case(&gitlab_project))
+    }
+
+    /// Host with the optional path, as `Gitlab` expects it, and whether https is used
+    pub(crate) fn host(&self) -> (&str, bool) {
+        let url = self.url.trim().trim_end_matches('/');
+        if let Some(host) = url.strip_prefix("https://") {
+            (host, true)
+        } else if let Some(host) = url.strip_prefix("http://") {
+            (host, false)
+        } else {
+            (url, true)
+        }
+    }
+
+    pub(crate) fn resolve_token(&self) -> GitlabResult<String> {
+        let token = match (&self.token, &self.token_env) {
+            (Some(token), _) if !token.trim().is_empty() => token.clone(),
+            (_, name) => {
+                let name = name.as_deref().unwrap_or(DEFAULT_TOKEN_ENV);
+                std::env::var(name).map_err(|_| GitlabToolsError::Config(format!("environment variable {} is not set", name)))?
+            }
+        };
+        Ok(token.trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::GitlabConfig;
+
+    #[test]
+    fn host_and_protocol() {
+        assert_eq!(GitlabConfig::new("https://git.let4.me/", "t").host(), ("git.let4.me", true));
+        assert_eq!(GitlabConfig::new("http://localhost:8080/gitlab", "t").host(), ("localhost:8080/gitlab", false));
+        assert_eq!(GitlabConfig::new("git.let4.me", "t").host(), ("git.let4.me", true));
+    }
+}
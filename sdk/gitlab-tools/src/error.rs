@@ -0,0 +1,60 @@
+use std::fmt;
+use gitlab::api::ApiError;
+
+pub type GitlabResult<T> = Result<T, GitlabToolsError>;
+
+#[derive(Debug)]
+pub enum GitlabToolsError {
+    /// Client isn't created: wrong url, token or certificates
+    Connection(gitlab::GitlabError),
+    Api(ApiError<gitlab::RestError>),
+    /// Parameters rejected by an endpoint builder, e.g. an empty project
+    Endpoint(String),
+    Config(String),
+    /// Runtime of `AsyncGitlabClient` isn't started or a request panicked
+    Runtime(String),
+}
+
+impl fmt::Display for GitlabToolsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitlabToolsError::Connection(error) => write!(f, "gitlab connection: {}", error),
+            GitlabToolsError::Api(error) => write!(f, "gitlab api: {}", error),
+            GitlabToolsError::Endpoint(message) => write!(f, "gitlab endpoint: {}", message),
+            GitlabToolsError::Config(message) => write!(f, "gitlab config: {}", message),
+            GitlabToolsError::Runtime(message) => write!(f, "gitlab runtime: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for GitlabToolsError {}
+
+impl GitlabToolsError {
+    pub fn is_not_found(&self) -> bool {
+        let status = match self {
+            GitlabToolsError::Api(ApiError::GitlabService { status, .. }) |
+            GitlabToolsError::Api(ApiError::GitlabWithStatus { status, .. }) |
+            GitlabToolsError::Api(ApiError::GitlabObjectWithStatus { status, .. }) |
+            GitlabToolsError::Api(ApiError::GitlabUnrecognizedWithStatus { status, .. }) => status.as_u16(),
+            _ => return false,
+        };
+        status == 404
+    }
+}
+
+impl From<gitlab::GitlabError> for GitlabToolsError {
+    fn from(error: gitlab::GitlabError) -> Self {
+        GitlabToolsError::Connection(error)
+    }
+}
+
+impl From<ApiError<gitlab::RestError>> for GitlabToolsError {
+    fn from(error: ApiError<gitlab::RestError>) -> Self {
+        GitlabToolsError::Api(error)
+    }
+}
+
+/// Builder errors of the endpoints are different types, only their message is kept
+pub(crate) fn endpoint_error<E: fmt::Display>(error: E) -> GitlabToolsError {
+    GitlabToolsError::Endpoint(error.to_string())
+}
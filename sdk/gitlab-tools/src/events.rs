@@ -0,0 +1,390 @@
+use std::fmt;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+/// Event of a project or system hook, the kind is taken from `object_kind` (`event_name` for system hooks)
+#[derive(Debug, Clone)]
+pub enum GitlabEvent {
+    MergeRequest(MergeRequestEvent),
+    Push(PushEvent),
+    TagPush(PushEvent),
+    Pipeline(PipelineEvent),
+    Note(NoteEvent),
+    Deployment(DeploymentEvent),
+    /// Kinds which aren't handled, e.g. `wiki_page` or `build`
+    Other(String),
+}
+
+#[derive(Debug)]
+pub struct EventError {
+    pub kind: String,
+    pub error: serde_json::Error,
+}
+
+impl fmt::Display for EventError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "wrong {} event: {}", self.kind, self.error)
+    }
+}
+
+impl std::error::Error for EventError {}
+
+impl GitlabEvent {
+    pub fn from_slice(body: &[u8]) -> Result<GitlabEvent, EventError> {
+        let value: Value = serde_json::from_slice(body).map_err(|error| EventError { kind: "unknown".to_string(), error })?;
+        let kind = value.get("object_kind").or_else(|| value.get("event_name"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        Ok(match kind.as_str() {
+            "merge_request" => GitlabEvent::MergeRequest(parse(&kind, value)?),
+            "push" => GitlabEvent::Push(parse(&kind, value)?),
+            "tag_push" => GitlabEvent::TagPush(parse(&kind, value)?),
+            "pipeline" => GitlabEvent::Pipeline(parse(&kind, value)?),
+            "note" => GitlabEvent::Note(parse(&kind, value)?),
+            "deployment" => GitlabEvent::Deployment(parse(&kind, value)?),
+            _ => GitlabEvent::Other(kind),
+        })
+    }
+
+    pub fn kind(&self) -> &str {
+        match self {
+            GitlabEvent::MergeRequest(_) => "merge_request",
+            GitlabEvent::Push(_) => "push",
+            GitlabEvent::TagPush(_) => "tag_push",
+            GitlabEvent::Pipeline(_) => "pipeline",
+            GitlabEvent::Note(_) => "note",
+            GitlabEvent::Deployment(_) => "deployment",
+            GitlabEvent::Other(kind) => kind,
+        }
+    }
+}
+
+fn parse<T: DeserializeOwned>(kind: &str, value: Value) -> Result<T, EventError> {
+    serde_json::from_value(value).map_err(|error| EventError { kind: kind.to_string(), error })
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct User {
+    pub id: Option<u64>,
+    pub name: String,
+    pub username: String,
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Project {
+    pub id: u64,
+    pub name: String,
+    pub path_with_namespace: String,
+    pub web_url: String,
+    pub default_branch: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommitAuthor {
+    pub name: String,
+    pub email: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct EventCommit {
+    pub id: String,
+    pub message: String,
+    pub title: Option<String>,
+    pub url: String,
+    pub timestamp: Option<String>,
+    pub author: Option<CommitAuthor>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Label {
+    pub title: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MergeRequestEvent {
+    pub user: User,
+    pub project: Project,
+    pub object_attributes: MergeRequestAttributes,
+    #[serde(default)]
+    pub labels: Vec<Label>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MergeRequestAttributes {
+    pub id: u64,
+    pub iid: u64,
+    pub title: String,
+    pub description: Option<String>,
+    /// `opened`, `closed`, `locked` or `merged`
+    pub state: String,
+    /// `open`, `close`, `reopen`, `update`, `approved`, `unapproved`, `merge`
+    pub action: Option<String>,
+    pub source_branch: String,
+    pub target_branch: String,
+    pub url: String,
+    pub merge_commit_sha: Option<String>,
+    pub last_commit: Option<EventCommit>,
+}
+
+impl MergeRequestEvent {
+    pub fn is_merged(&self) -> bool {
+        self.object_attributes.action.as_deref() == Some("merge")
+    }
+}
+
+/// Push and tag push
+#[derive(Deserialize, Debug, Clone)]
+pub struct PushEvent {
+    pub before: String,
+    pub after: String,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    pub checkout_sha: Option<String>,
+    pub user_name: Option<String>,
+    pub user_username: Option<String>,
+    pub project: Project,
+    #[serde(default)]
+    pub commits: Vec<EventCommit>,
+    #[serde(default)]
+    pub total_commits_count: u64,
+}
+
+impl PushEvent {
+    /// `master` for `refs/heads/master`
+    pub fn branch(&self) -> Option<&str> {
+        self.ref_.strip_prefix("refs/heads/")
+    }
+
+    /// `v1.4.0` for `refs/tags/v1.4.0`
+    pub fn tag(&self) -> Option<&str> {
+        self.ref_.strip_prefix("refs/tags/")
+    }
+
+    /// Ref is deleted, `after` is all zeros
+    pub fn is_removal(&self) -> bool {
+        self.after.chars().all(|char| char == '0')
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PipelineEvent {
+    pub object_attributes: PipelineAttributes,
+    pub merge_request: Option<PipelineMergeRequest>,
+    pub user: Option<User>,
+    pub project: Project,
+    pub commit: Option<EventCommit>,
+    #[serde(default)]
+    pub builds: Vec<Build>,
+}
+
+impl PipelineEvent {
+    /// Branch the pipeline ran for, the source branch for merge request pipelines and `None` for tags
+    pub fn branch(&self) -> Option<&str> {
+        if self.object_attributes.tag {
+            return None;
+        }
+        match &self.merge_request {
+            Some(merge_request) => Some(&merge_request.source_branch),
+            None => Some(self.object_attributes.ref_.strip_prefix("refs/heads/").unwrap_or(&self.object_attributes.ref_)),
+        }
+    }
+
+    pub fn web_url(&self) -> String {
+        format!("{}/-/pipelines/{}", self.project.web_url, self.object_attributes.id)
+    }
+
+    pub fn job_url(&self, build: &Build) -> String {
+        format!("{}/-/jobs/{}", self.project.web_url, build.id)
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PipelineAttributes {
+    pub id: u64,
+    #[serde(rename = "ref")]
+    pub ref_: String,
+    #[serde(default)]
+    pub tag: bool,
+    pub sha: String,
+    /// `pending`, `running`, `success`, `failed`, `canceled`, `skipped`...
+    pub status: String,
+    pub detailed_status: Option<String>,
+    pub source: Option<String>,
+    pub duration: Option<u64>,
+    pub created_at: Option<String>,
+    pub finished_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct PipelineMergeRequest {
+    pub id: u64,
+    pub iid: u64,
+    pub title: String,
+    pub source_branch: String,
+    pub target_branch: String,
+    pub url: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Build {
+    pub id: u64,
+    pub stage: String,
+    pub name: String,
+    pub status: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NoteEvent {
+    pub user: User,
+    pub project: Project,
+    pub object_attributes: NoteAttributes,
+    pub merge_request: Option<NoteMergeRequest>,
+    pub commit: Option<EventCommit>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NoteAttributes {
+    pub id: u64,
+    pub note: String,
+    /// `MergeRequest`, `Commit`, `Issue` or `Snippet`
+    pub noteable_type: String,
+    pub url: String,
+    pub discussion_id: Option<String>,
+    #[serde(default)]
+    pub system: bool,
+    /// `create` or `update`, sent by newer gitlab versions only
+    pub action: Option<String>,
+    /// Place of a diff note
+    pub position: Option<NotePosition>,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NotePosition {
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub old_line: Option<u64>,
+    pub new_line: Option<u64>,
+}
+
+impl NotePosition {
+    /// `src/login.rs:42`, a removed line is shown by its old path and line
+    pub fn location(&self) -> Option<String> {
+        match (&self.new_path, self.new_line, &self.old_path, self.old_line) {
+            (Some(path), Some(line), _, _) | (_, _, Some(path), Some(line)) => Some(format!("{}:{}", path, line)),
+            (Some(path), None, _, _) => Some(path.clone()),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct NoteMergeRequest {
+    pub id: u64,
+    pub iid: u64,
+    pub title: String,
+    pub description: Option<String>,
+    pub source_branch: String,
+    pub target_branch: String,
+    pub state: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct DeploymentEvent {
+    /// `running`, `success`, `failed` or `canceled`
+    pub status: String,
+    pub deployment_id: Option<u64>,
+    pub deployable_id: Option<u64>,
+    pub deployable_url: Option<String>,
+    pub environment: String,
+    pub project: Project,
+    pub short_sha: String,
+    pub user: Option<User>,
+    pub commit_url: Option<String>,
+    pub commit_title: Option<String>,
+    #[serde(rename = "ref")]
+    pub ref_: Option<String>,
+}
+
+/// Recorded payloads of `src/events` for tests of event handlers, other crates enable them by the `test-support` feature
+#[cfg(any(test, feature = "test-support"))]
+pub mod samples {
+    use crate::events::{DeploymentEvent, GitlabEvent, MergeRequestEvent, NoteEvent, PipelineEvent, PushEvent};
+
+    /// Body of the hook as gitlab sends it, `name` is the `object_kind` of the event
+    pub fn payload(name: &str) -> &'static [u8] {
+        match name {
+            "merge_request" => include_bytes!("events/merge_request.json"),
+            "push" => include_bytes!("events/push.json"),
+            "tag_push" => include_bytes!("events/tag_push.json"),
+            "pipeline" => include_bytes!("events/pipeline.json"),
+            "note" => include_bytes!("events/note.json"),
+            "deployment" => include_bytes!("events/deployment.json"),
+            other => panic!("no recorded {} event", other),
+        }
+    }
+
+    pub fn sample_event(name: &str) -> GitlabEvent {
+        GitlabEvent::from_slice(payload(name)).unwrap()
+    }
+
+    macro_rules! sample {
+        ($name:ident, $variant:ident, $event:ty) => {
+            pub fn $name() -> $event {
+                match sample_event(stringify!($name)) {
+                    GitlabEvent::$variant(event) => event,
+                    other => panic!("unexpected event {:?}", other),
+                }
+            }
+        };
+    }
+
+    sample!(merge_request, MergeRequest, MergeRequestEvent);
+    sample!(push, Push, PushEvent);
+    sample!(tag_push, TagPush, PushEvent);
+    sample!(pipeline, Pipeline, PipelineEvent);
+    sample!(note, Note, NoteEvent);
+    sample!(deployment, Deployment, DeploymentEvent);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::events::{samples, GitlabEvent};
+
+    #[test]
+    fn recorded_payloads() {
+        let event = samples::merge_request();
+        assert!(event.is_merged());
+        assert_eq!(event.object_attributes.iid, 12);
+        assert_eq!(event.object_attributes.source_branch, "feature/SSP-7-short-summary");
+        assert_eq!(event.object_attributes.merge_commit_sha.as_deref(), Some("2b4d1c5b2e6b3d1d0e0ac0c1a7c1b9b6d1b0e7f3"));
+
+        let event = samples::push();
+        assert_eq!(event.branch(), Some("feature/SSP-7-short-summary"));
+        assert_eq!(event.commits.len(), 2);
+
+        assert_eq!(samples::tag_push().tag(), Some("v1.4.0"));
+
+        let event = samples::pipeline();
+        assert_eq!(event.object_attributes.status, "failed");
+        assert_eq!(event.branch(), Some("feature/SSP-7-short-summary"));
+        assert_eq!(event.job_url(&event.builds[0]), "https://git.let4.me/serge/proj2/-/jobs/1021");
+        assert_eq!(event.builds.len(), 2);
+        assert_eq!(event.merge_request.map(|merge_request| merge_request.iid), Some(12));
+
+        let event = samples::note();
+        assert_eq!(event.object_attributes.noteable_type, "MergeRequest");
+        assert_eq!(event.merge_request.map(|merge_request| merge_request.iid), Some(12));
+
+        let event = samples::deployment();
+        assert_eq!((event.status.as_str(), event.environment.as_str()), ("success", "staging"));
+
+        assert_eq!(GitlabEvent::from_slice(br#"{"object_kind": "wiki_page"}"#).unwrap().kind(), "wiki_page");
+        assert!(GitlabEvent::from_slice(br#"{"object_kind": "push"}"#).is_err());
+    }
+}
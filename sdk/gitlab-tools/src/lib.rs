@@ -1,39 +1,21 @@
 extern crate gitlab;
 
-use gitlab::{Gitlab, api};
-use gitlab::api::projects::merge_requests::CreateMergeRequest;
-use gitlab::api::Query;
-
-
-pub fn create_client() -> Gitlab {
-    Gitlab::new("git.let4.me", "token").unwrap()
-}
-
-pub fn prepare_merge_request<'a>() -> CreateMergeRequest<'a> {
-    CreateMergeRequest::builder()
-        .project("serge/proj2")
-        .title("mr title")
-        .description("description")
-        .remove_source_branch(true)
-        .squash(true)
-        // .project(NameOrId::Name(Cow::from("proj2")))
-        .source_branch("branch1")
-        .target_branch("master")
-        .build()
-        .unwrap()
-}
-
-pub fn create_merge_request(client: &Gitlab, merge_request:CreateMergeRequest){
-    api::ignore(merge_request).query(client).unwrap();
-}
+pub mod release;
+pub mod config;
+pub mod client;
+pub mod async_client;
+pub mod error;
+pub mod merge_request;
+pub mod branch;
+pub mod events;
 
 #[cfg(test)]
 mod tests {
-    use gitlab::{Gitlab, Project, api, MergeRequest};
+    use gitlab::{Gitlab, api};
     use gitlab::api::{projects, Query};
     use gitlab::api::projects::merge_requests::CreateMergeRequest;
-    use gitlab::api::common::NameOrId;
-    use gitlab::api::endpoint_prelude::Cow;
+    // typed models left the gitlab crate, responses are read as json
+    use serde_json::Value as Project;
 
     #[test]
     fn it_works() {
@@ -42,6 +24,7 @@ mod tests {
 
     #[test]
     #[ignore]
+    #[allow(unused_variables)]
     pub fn some() {
         let client = Gitlab::new("git.let4.me", "token").unwrap();
         // Create a simple endpoint. This one gets the "gitlab-org/gitlab" project information.
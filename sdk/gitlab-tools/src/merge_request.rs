@@ -0,0 +1,51 @@
+use serde::Deserialize;
+
+/// Branch names longer than this are cut at a word boundary
+const MAX_BRANCH_SLUG: usize = 40;
+
+/// Merge request as returned by gitlab
+#[derive(Deserialize, Debug, Clone)]
+pub struct MergeRequest {
+    pub id: u64,
+    pub iid: u64,
+    pub project_id: u64,
+    pub title: String,
+    pub description: Option<String>,
+    pub state: String,
+    pub source_branch: String,
+    pub target_branch: String,
+    pub web_url: String,
+}
+
+/// `feature/SSP-7-short-summary`
+pub fn source_branch(prefix: &str, issue_id: &str, summary: &str) -> String {
+    let mut slug = String::new();
+    for word in summary.to_lowercase().split(|char: char| !char.is_ascii_alphanumeric()).filter(|word| !word.is_empty()) {
+        if !slug.is_empty() && slug.len() + word.len() + 1 > MAX_BRANCH_SLUG {
+            break;
+        }
+        if !slug.is_empty() {
+            slug.push('-');
+        }
+        slug.push_str(word);
+    }
+    slug.truncate(MAX_BRANCH_SLUG);
+    if slug.is_empty() {
+        format!("{}{}", prefix, issue_id)
+    } else {
+        format!("{}{}-{}", prefix, issue_id, slug)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::merge_request::source_branch;
+
+    #[test]
+    fn branch_names() {
+        assert_eq!(source_branch("feature/", "SSP-7", "Short summary"), "feature/SSP-7-short-summary");
+        assert_eq!(source_branch("feature/", "SSP-7", "Fix: login (OAuth) fails on Safari/iOS 14 when the session cookie expires"),
+                   "feature/SSP-7-fix-login-oauth-fails-on-safari-ios-14");
+        assert_eq!(source_branch("", "SSP-7", "Привет"), "SSP-7");
+    }
+}
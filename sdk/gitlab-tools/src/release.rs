@@ -0,0 +1,77 @@
+use gitlab::Gitlab;
+use gitlab::api::{self, Query};
+use gitlab::api::common::SortOrder;
+use gitlab::api::projects::repository::tags::{Tags, TagsOrderBy};
+use gitlab::api::projects::repository::commits::{Commits, CompareCommits};
+use serde::Deserialize;
+use crate::error::{endpoint_error, GitlabResult};
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Tag {
+    pub name: String,
+    pub commit: Commit,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Commit {
+    pub id: String,
+    pub title: String,
+    pub message: String,
+    pub author_name: Option<String>,
+    pub author_email: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct Comparison {
+    pub(crate) commits: Vec<Commit>,
+}
+
+/// Tag released before `tag`, tags are compared in version order (`v1.3.2` < `v1.4.0`)
+pub fn previous_tag(client: &Gitlab, project: &str, tag: &str) -> GitlabResult<Option<Tag>> {
+    let tags: Vec<Tag> = api::paged(tags_endpoint(project)?, api::Pagination::All).query(client)?;
+    Ok(previous_of(tags, tag))
+}
+
+/// Commits which are in `tag` but not in the previous tag, all commits of the tag for the first release
+pub fn commits_since_previous_tag(client: &Gitlab, project: &str, tag: &str) -> GitlabResult<Vec<Commit>> {
+    match previous_tag(client, project, tag)? {
+        Some(previous) => {
+            let comparison: Comparison = compare_endpoint(project, previous.name, tag)?.query(client)?;
+            Ok(comparison.commits)
+        }
+        None => Ok(api::paged(commits_endpoint(project, tag)?, api::Pagination::All).query(client)?),
+    }
+}
+
+pub(crate) fn tags_endpoint(project: &str) -> GitlabResult<Tags<'_>> {
+    Tags::builder()
+        .project(project)
+        .order_by(TagsOrderBy::Version)
+        .sort(SortOrder::Descending)
+        .build()
+        .map_err(endpoint_error)
+}
+
+/// Tags are sorted from the newest
+pub(crate) fn previous_of(tags: Vec<Tag>, tag: &str) -> Option<Tag> {
+    tags.into_iter()
+        .skip_while(|candidate| candidate.name != tag)
+        .nth(1)
+}
+
+pub(crate) fn compare_endpoint<'a>(project: &'a str, from: String, to: &'a str) -> GitlabResult<CompareCommits<'a>> {
+    CompareCommits::builder()
+        .project(project)
+        .from(from)
+        .to(to)
+        .build()
+        .map_err(endpoint_error)
+}
+
+pub(crate) fn commits_endpoint<'a>(project: &'a str, tag: &'a str) -> GitlabResult<Commits<'a>> {
+    Commits::builder()
+        .project(project)
+        .ref_name(tag)
+        .build()
+        .map_err(endpoint_error)
+}
@@ -0,0 +1,140 @@
+use std::collections::HashSet;
+use std::ops::Range;
+use regex::Regex;
+
+lazy_static! {
+    static ref KEY: Regex = Regex::new(r"(?i)\b([a-z][a-z0-9_]*)-(\d+)\b").unwrap();
+    static ref VERB: Regex = Regex::new(r"(?i)\b(fix|fixe[sd]|close[sd]?|resolve[sd]?|refs?|references?|see|relates?\s+to)\s*:?\s*#?$").unwrap();
+    /// Text between two keys of one verb: `fixes SSP-7, SSP-8 and #SSP-9`
+    static ref LIST_SEPARATOR: Regex = Regex::new(r"(?i)^\s*(,|&|\band\b)?\s*#?$").unwrap();
+}
+
+/// What a commit or a merge request does with the issue, given by the verb before the key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Intent {
+    /// Key without a verb
+    Mention,
+    /// `refs`, `see`, `relates to`
+    Reference,
+    /// `fixes`, `closes`, `resolves`
+    Close,
+}
+
+impl Intent {
+    fn from_verb(verb: &str) -> Intent {
+        let verb = verb.to_lowercase();
+        if verb.starts_with("fix") || verb.starts_with("close") || verb.starts_with("resolve") {
+            Intent::Close
+        } else {
+            Intent::Reference
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueKey {
+    /// Upper case short name of the project, `SSP`
+    pub project: String,
+    pub number: u64,
+    pub intent: Intent,
+    /// Byte range of the key in the text, without the `#` of `#SSP-9`
+    pub span: Range<usize>,
+}
+
+impl IssueKey {
+    /// `SSP-7`
+    pub fn id(&self) -> String {
+        format!("{}-{}", self.project, self.number)
+    }
+}
+
+/// Finds keys of the known youtrack projects in branch names, commit messages and merge request text.
+/// Words like `UTF-8` or `SHA-256` look like keys too, so only the known short names are accepted
+#[derive(Debug, Clone, Default)]
+pub struct IssueKeyParser {
+    projects: HashSet<String>,
+}
+
+impl IssueKeyParser {
+    pub fn new<I, S>(projects: I) -> Self where I: IntoIterator<Item=S>, S: AsRef<str> {
+        IssueKeyParser { projects: projects.into_iter().map(|project| project.as_ref().to_uppercase()).collect() }
+    }
+
+    /// Every mention in order of position
+    pub fn parse(&self, text: &str) -> Vec<IssueKey> {
+        let mut keys: Vec<IssueKey> = Vec::new();
+        for captures in KEY.captures_iter(text) {
+            let project = captures[1].to_uppercase();
+            if !self.projects.contains(&project) {
+                continue;
+            }
+            let number = match captures[2].parse() {
+                Ok(number) => number,
+                Err(_) => continue,
+            };
+            let span = captures.get(0).unwrap().range();
+            let line_start = text[..span.start].rfind('\n').map(|index| index + 1).unwrap_or(0);
+            let intent = match keys.last() {
+                Some(previous) if previous.span.end >= line_start && previous.intent != Intent::Mention
+                    && LIST_SEPARATOR.is_match(&text[previous.span.end..span.start]) => previous.intent,
+                _ => VERB.captures(&text[line_start..span.start])
+                    .map(|verb| Intent::from_verb(&verb[1]))
+                    .unwrap_or(Intent::Mention),
+            };
+            keys.push(IssueKey { project, number, intent, span });
+        }
+        keys
+    }
+
+    /// Unique ids like `SSP-7` mentioned by any of the texts, in order of the first mention
+    pub fn issue_ids<S: AsRef<str>>(&self, texts: &[S]) -> Vec<String> {
+        let mut issue_ids: Vec<String> = Vec::new();
+        for text in texts {
+            for key in self.parse(text.as_ref()) {
+                let issue_id = key.id();
+                if !issue_ids.contains(&issue_id) {
+                    issue_ids.push(issue_id);
+                }
+            }
+        }
+        issue_ids
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::issue_keys::{Intent, IssueKeyParser};
+
+    fn parse(text: &str) -> Vec<(String, Intent, &str)> {
+        IssueKeyParser::new(["SSP", "web"]).parse(text).into_iter()
+            .map(|key| (key.id(), key.intent, &text[key.span]))
+            .collect()
+    }
+
+    #[test]
+    fn keys_of_known_projects() {
+        assert_eq!(parse("feature/ssp-12-search"), vec![("SSP-12".to_string(), Intent::Mention, "ssp-12")]);
+        assert_eq!(parse("Store as UTF-8, hash with SHA-256 for WEB-3"), vec![("WEB-3".to_string(), Intent::Mention, "WEB-3")]);
+        assert!(parse("XSSP-7 SSP-7a SSP-").is_empty());
+    }
+
+    #[test]
+    fn magic_verbs() {
+        let text = "Login fixes SSP-7, SSP-8 and #SSP-9\nrefs: SSP-10 see WEB-1\nSSP-11 closes SSP-12";
+        assert_eq!(parse(text).into_iter().map(|(id, intent, _)| (id, intent)).collect::<Vec<_>>(), vec![
+            ("SSP-7".to_string(), Intent::Close),
+            ("SSP-8".to_string(), Intent::Close),
+            ("SSP-9".to_string(), Intent::Close),
+            ("SSP-10".to_string(), Intent::Reference),
+            ("WEB-1".to_string(), Intent::Reference),
+            ("SSP-11".to_string(), Intent::Mention),
+            ("SSP-12".to_string(), Intent::Close),
+        ]);
+    }
+
+    #[test]
+    fn unique_ids() {
+        let messages = ["SSP-7: fix login", "Merge branch 'feature/ssp-12-search' into 'master'\n\nCloses SSP-7"];
+        assert_eq!(IssueKeyParser::new(["SSP"]).issue_ids(&messages), vec!["SSP-7".to_string(), "SSP-12".to_string()]);
+    }
+}
@@ -1,3 +1,9 @@
+#[macro_use]
+extern crate lazy_static;
+
+pub mod issue_keys;
+pub mod smart_commits;
+
 #[cfg(test)]
 mod tests {
 	#[test]
@@ -0,0 +1,143 @@
+use regex::Regex;
+use crate::issue_keys::IssueKeyParser;
+
+lazy_static! {
+    static ref DIRECTIVE: Regex = Regex::new(r"(?:^|\s)#([A-Za-z][A-Za-z0-9_-]*)").unwrap();
+    static ref ISSUE_KEY: Regex = Regex::new(r"^[A-Za-z][A-Za-z0-9_]*-\d+$").unwrap();
+    static ref DURATION: Regex = Regex::new(r"(?i)^((?:\d+[wdhm])+(?:\s+(?:\d+[wdhm])+)*)(?:\s+|$)").unwrap();
+    static ref DURATION_PART: Regex = Regex::new(r"(?i)(\d+)([wdhm])").unwrap();
+}
+
+/// Youtrack defaults: 8 hours a day, 5 days a week
+const MINUTES_IN_DAY: u64 = 8 * 60;
+const MINUTES_IN_WEEK: u64 = 5 * MINUTES_IN_DAY;
+
+/// Command written after `#` in a commit message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directive {
+    /// `#fixed`, `#in-progress` is `In progress`: the name of the new state, dashes are spaces.
+    /// Only words accepted by `is_state` of `parse_smart_commit` are transitions
+    Transition(String),
+    /// `#time 1h30m text of the work item`
+    Time { minutes: u64, text: Option<String> },
+    /// `#comment text till the end of the line`
+    Comment(String),
+}
+
+/// `SSP-7 #fixed #time 1h30m #comment reviewed with QA`, directives are applied to every issue of the message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SmartCommit {
+    pub issue_ids: Vec<String>,
+    pub directives: Vec<Directive>,
+}
+
+/// `None` when the message has no keys of the known projects or no directives.
+/// `is_state` gets the state name of `#word` (`in progress` for `#in-progress`), other words stay text,
+/// e.g. `#wip` in `#comment ready #wip` is a part of the comment
+pub fn parse_smart_commit(keys: &IssueKeyParser, message: &str, is_state: impl Fn(&str) -> bool) -> Option<SmartCommit> {
+    let issue_ids = keys.issue_ids(&[message]);
+    if issue_ids.is_empty() {
+        return None;
+    }
+    let mut directives = Vec::new();
+    for line in message.lines() {
+        // `#SSP-9` is a key after a magic verb, not a directive
+        let found: Vec<_> = DIRECTIVE.captures_iter(line)
+            .map(|captures| captures.get(1).unwrap())
+            .filter(|name| !ISSUE_KEY.is_match(name.as_str()))
+            .filter(|name| matches!(name.as_str().to_lowercase().as_str(), "comment" | "time") || is_state(&state_name(name.as_str())))
+            .collect();
+        for (index, name) in found.iter().enumerate() {
+            let argument_end = found.get(index + 1).map(|next| next.start() - 1).unwrap_or(line.len());
+            let argument = line[name.end()..argument_end].trim();
+            let directive = match name.as_str().to_lowercase().as_str() {
+                "comment" if argument.is_empty() => continue,
+                "comment" => Directive::Comment(argument.to_string()),
+                "time" => match parse_duration(argument) {
+                    Some((minutes, text)) => Directive::Time { minutes, text: Some(text.to_string()).filter(|text| !text.is_empty()) },
+                    None => continue,
+                },
+                state => Directive::Transition(state_name(state)),
+            };
+            directives.push(directive);
+        }
+    }
+    if directives.is_empty() {
+        None
+    } else {
+        Some(SmartCommit { issue_ids, directives })
+    }
+}
+
+/// `in progress` of `#in-progress`
+fn state_name(word: &str) -> String {
+    word.to_lowercase().replace('-', " ")
+}
+
+/// `1w 2d 4h 30m` or `1h30m` in minutes and the text after the duration
+pub fn parse_duration(text: &str) -> Option<(u64, &str)> {
+    let captures = DURATION.captures(text.trim_start())?;
+    let duration = captures.get(1).unwrap();
+    let minutes = DURATION_PART.captures_iter(duration.as_str())
+        .map(|part| {
+            let value: u64 = part[1].parse().unwrap_or(0);
+            match part[2].to_ascii_lowercase().as_str() {
+                "w" => value * MINUTES_IN_WEEK,
+                "d" => value * MINUTES_IN_DAY,
+                "h" => value * 60,
+                _ => value,
+            }
+        })
+        .sum();
+    Some((minutes, text.trim_start()[captures.get(0).unwrap().end()..].trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::issue_keys::IssueKeyParser;
+    use crate::smart_commits::{parse_duration, parse_smart_commit, Directive, SmartCommit};
+
+    const STATES: [&str; 3] = ["open", "in progress", "fixed"];
+
+    fn is_state(name: &str) -> bool {
+        STATES.contains(&name)
+    }
+
+    #[test]
+    fn directives() {
+        let keys = IssueKeyParser::new(["SSP"]);
+        let message = "SSP-7 #fixed #time 1h30m #comment reviewed with QA\n\nAlso fixes #SSP-9 #in-progress #time 2d pairing";
+        assert_eq!(parse_smart_commit(&keys, message, is_state), Some(SmartCommit {
+            issue_ids: vec!["SSP-7".to_string(), "SSP-9".to_string()],
+            directives: vec![
+                Directive::Transition("fixed".to_string()),
+                Directive::Time { minutes: 90, text: None },
+                Directive::Comment("reviewed with QA".to_string()),
+                Directive::Transition("in progress".to_string()),
+                Directive::Time { minutes: 960, text: Some("pairing".to_string()) },
+            ],
+        }));
+        assert_eq!(parse_smart_commit(&keys, "SSP-7: fix login", is_state), None);
+        assert_eq!(parse_smart_commit(&keys, "#fixed without issue", is_state), None);
+    }
+
+    #[test]
+    fn unknown_words_are_text() {
+        let keys = IssueKeyParser::new(["SSP"]);
+        assert_eq!(parse_smart_commit(&keys, "SSP-7 #wip login form", is_state), None);
+        assert_eq!(parse_smart_commit(&keys, "SSP-7 #comment ready for #qa, see #42 #Fixed", is_state), Some(SmartCommit {
+            issue_ids: vec!["SSP-7".to_string()],
+            directives: vec![
+                Directive::Comment("ready for #qa, see #42".to_string()),
+                Directive::Transition("fixed".to_string()),
+            ],
+        }));
+    }
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("1w 2d 4h 30m"), Some((3630, "")));
+        assert_eq!(parse_duration("45m code review"), Some((45, "code review")));
+        assert_eq!(parse_duration("2 days"), None);
+    }
+}
@@ -0,0 +1,130 @@
+//! Synchronous wrappers over the async client, every call is executed on an internal tokio runtime.
+//! Requires the `blocking` feature
+use std::ops::{Deref, DerefMut};
+use std::sync::{Arc, Mutex};
+use std::future::Future;
+use tokio::runtime::Runtime;
+use crate::rest_api::base::NameType;
+use crate::rest_api::base::ops::BaseOps;
+use crate::rest_api::client::{Config, YoutrackClient as AsyncYoutrackClient, YoutrackClientImpl};
+use crate::rest_api::issue::Issue as AsyncIssue;
+use crate::rest_api::json_models::issue::field::IssueStateType;
+use crate::rest_api::json_models::issue::field::custom_field::StateMachineEvent;
+use crate::rest_api::error::YoutrackResult;
+
+type SharedRuntime = Arc<Mutex<Runtime>>;
+
+fn block_on<F: Future>(runtime: &SharedRuntime, future: F) -> F::Output {
+    runtime.lock().unwrap().block_on(future)
+}
+
+pub struct YoutrackClient {
+    inner: YoutrackClientImpl,
+    runtime: SharedRuntime,
+}
+
+impl YoutrackClient {
+    /// Client with a permanent token
+    pub fn new(domain: String, bearer_token: String) -> YoutrackResult<YoutrackClient> {
+        YoutrackClient::from_config(Config::new(domain, bearer_token))
+    }
+
+    /// Client with any auth provider, proxy and headers of the config
+    pub fn from_config(config: Config) -> YoutrackResult<YoutrackClient> {
+        let runtime = tokio::runtime::Builder::new()
+            .basic_scheduler()
+            .enable_all()
+            .build()?;
+        let inner = YoutrackClientImpl::from_config(config);
+        Ok(YoutrackClient { inner, runtime: Arc::new(Mutex::new(runtime)) })
+    }
+
+    pub fn issue(&self, name: NameType) -> YoutrackResult<Issue> {
+        let inner = block_on(&self.runtime, self.inner.issue(name))?;
+        Ok(Issue { inner, runtime: self.runtime.clone() })
+    }
+}
+
+/// Blocking version of the `Issue` active record, fields are reachable through `Deref`
+pub struct Issue {
+    inner: Box<AsyncIssue>,
+    runtime: SharedRuntime,
+}
+
+impl Issue {
+    pub fn update(&mut self) -> YoutrackResult<&mut Self> {
+        block_on(&self.runtime, self.inner.update())?;
+        Ok(self)
+    }
+
+    pub fn save(&mut self) -> YoutrackResult<&mut Self> {
+        block_on(&self.runtime, self.inner.save())?;
+        Ok(self)
+    }
+
+    pub fn set_state_name(&mut self, status_name: String) -> YoutrackResult<()> {
+        self.inner.set_state_name(status_name)
+    }
+
+    pub fn set_state(&mut self, state_type: IssueStateType) -> YoutrackResult<()> {
+        self.inner.set_state(state_type)
+    }
+
+    pub fn allowed_transitions(&self) -> YoutrackResult<Vec<StateMachineEvent>> {
+        block_on(&self.runtime, self.inner.allowed_transitions())
+    }
+
+    pub fn transition(&mut self, event: &StateMachineEvent) -> YoutrackResult<&mut Self> {
+        block_on(&self.runtime, self.inner.transition(event))?;
+        Ok(self)
+    }
+
+    pub fn into_async(self) -> Box<AsyncIssue> {
+        self.inner
+    }
+}
+
+impl Deref for Issue {
+    type Target = AsyncIssue;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl DerefMut for Issue {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use crate::blocking::YoutrackClient;
+    use crate::rest_api::client::Config;
+
+    /// Answers one request with the issue fixture, returns the host to connect
+    fn serve_issue() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            // GET without a body, the request ends with an empty line
+            BufReader::new(&stream).lines().map(Result::unwrap).find(String::is_empty);
+            let body = include_str!("rest_api/json_models/issue.json");
+            write!(stream, "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", body.len(), body).unwrap();
+        });
+        host
+    }
+
+    #[test]
+    fn issue_is_fetched_without_runtime_of_caller() {
+        let config = Config::new(serve_issue(), "perm:dGVzdA==.MS0x.xxxx".to_string()).with_proxy(None);
+        let client = YoutrackClient::from_config(config).unwrap();
+        let issue = client.issue("SSP-1".to_string()).unwrap();
+        assert_eq!(issue.id_readable.as_deref(), Some("SSP-1"));
+        assert_eq!(issue.summary.as_deref(), Some("First task"));
+    }
+}
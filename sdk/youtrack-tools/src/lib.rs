@@ -1,10 +1,14 @@
 #![feature(box_syntax)]
 
 pub mod rest_api;
+#[cfg(feature = "blocking")]
+pub mod blocking;
 
 extern crate async_trait;
 extern crate serde;
 extern crate log;
+#[macro_use]
+extern crate lazy_static;
 
 #[cfg(test)]
 mod tests {
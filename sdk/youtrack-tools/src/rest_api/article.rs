@@ -0,0 +1,51 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use crate::rest_api::base::ops::BaseOps;
+use crate::rest_api::base::wrap::ActiveRecordWrap;
+use crate::rest_api::error::YoutrackResult;
+use crate::rest_api::json_models::article::{ArticleDto, ArticleAttachment, ArticleRef, ArticleUpdate};
+use crate::rest_api::service::articles::{fetch_article, update_article, fetch_child_articles, attach_file};
+
+/// Knowledge base article, `content` is markdown
+pub type Article = ActiveRecordWrap<ArticleDto>;
+
+#[async_trait]
+impl BaseOps for Article {
+    async fn update(&mut self) -> YoutrackResult<&mut Self> {
+        let new_origin = fetch_article(&self.http_client, &self.origin.id).await?;
+        self.refresh(new_origin);
+        Ok(self)
+    }
+
+    async fn save(&mut self) -> YoutrackResult<&mut Self> {
+        self.persist().await
+    }
+}
+
+impl Article {
+    /// Sends summary, content and parent when some of them were changed
+    pub async fn persist(&mut self) -> YoutrackResult<&mut Self> {
+        let update = ArticleUpdate::new(&self.inner);
+        if update != ArticleUpdate::new(&self.origin) {
+            let new_origin = update_article(&self.http_client, &self.origin.id, update).await?;
+            self.refresh(new_origin);
+        }
+        Ok(self)
+    }
+
+    pub fn set_parent(&mut self, parent_id: String) {
+        Arc::make_mut(&mut self.inner).parent_article = Some(ArticleRef { id: parent_id, ..ArticleRef::default() });
+    }
+
+    pub async fn children(&self) -> YoutrackResult<Vec<ArticleRef>> {
+        fetch_child_articles(&self.http_client, &self.origin.id).await
+    }
+
+    /// Uploads file and reloads the article, so `attachments` contains it
+    pub async fn attach(&mut self, file_name: &str, mime_type: &str, data: &[u8]) -> YoutrackResult<Vec<ArticleAttachment>> {
+        let attachments = attach_file(&self.http_client, &self.origin.id, file_name, mime_type, data).await?;
+        let new_origin = fetch_article(&self.http_client, &self.origin.id).await?;
+        self.refresh(new_origin);
+        Ok(attachments)
+    }
+}
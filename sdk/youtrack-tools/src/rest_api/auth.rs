@@ -0,0 +1,210 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use async_trait::async_trait;
+use hyper::{Body, Method, Request};
+use serde::Deserialize;
+use crate::rest_api::error::{YoutrackError, YoutrackResult};
+use crate::rest_api::base::proxy::{build_client, HyperClient, ProxyConfig};
+
+/// Token is refreshed this long before it expires, so requests in flight don't get 401
+const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
+/// Source of the `Authorization` header, asked before every request
+#[async_trait]
+pub trait AuthProvider: fmt::Debug + Send + Sync {
+    async fn authorization(&self) -> YoutrackResult<String>;
+}
+
+/// Permanent token from the youtrack profile, `perm:...`
+pub struct PermanentToken(String);
+
+impl PermanentToken {
+    pub fn new(token: String) -> Self {
+        PermanentToken(token)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for PermanentToken {
+    async fn authorization(&self) -> YoutrackResult<String> {
+        Ok(format!("Bearer {}", self.0))
+    }
+}
+
+impl fmt::Debug for PermanentToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PermanentToken(***)")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TokenSource {
+    /// File with the token, e.g. a mounted docker secret
+    File(PathBuf),
+    /// Name of the environment variable
+    Env(String),
+}
+
+/// Permanent token which is read on the first request and kept in memory
+pub struct LazyToken {
+    source: TokenSource,
+    token: Mutex<Option<String>>,
+}
+
+impl LazyToken {
+    pub fn new(source: TokenSource) -> Self {
+        LazyToken { source, token: Mutex::new(None) }
+    }
+
+    fn read(&self) -> YoutrackResult<String> {
+        let token = match &self.source {
+            TokenSource::File(path) => std::fs::read_to_string(path)?,
+            TokenSource::Env(name) => std::env::var(name)
+                .map_err(|_| YoutrackError::Auth(format!("environment variable {} is not set", name)))?,
+        };
+        let token = token.trim().to_string();
+        if token.is_empty() {
+            return Err(YoutrackError::Auth(format!("token from {:?} is empty", self.source)));
+        }
+        Ok(token)
+    }
+}
+
+#[async_trait]
+impl AuthProvider for LazyToken {
+    async fn authorization(&self) -> YoutrackResult<String> {
+        let mut token = self.token.lock().unwrap();
+        if token.is_none() {
+            *token = Some(self.read()?);
+        }
+        Ok(format!("Bearer {}", token.as_deref().unwrap_or_default()))
+    }
+}
+
+impl fmt::Debug for LazyToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LazyToken").field("source", &self.source).finish()
+    }
+}
+
+/// OAuth2 client-credentials flow of JetBrains Hub, the service (youtrack) acts on its own behalf
+pub struct HubClientCredentials {
+    /// Hub url, e.g. `https://youtrack.example.com/hub`
+    hub_url: String,
+    client_id: String,
+    client_secret: String,
+    /// Id of the youtrack service in hub
+    scope: String,
+    http: HyperClient,
+    token: Mutex<Option<AccessToken>>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Clone)]
+struct AccessToken {
+    value: String,
+    expires_at: Instant,
+}
+
+impl AccessToken {
+    fn is_fresh(&self, now: Instant) -> bool {
+        now + REFRESH_MARGIN < self.expires_at
+    }
+}
+
+impl HubClientCredentials {
+    pub fn new(hub_url: String, client_id: String, client_secret: String, scope: String) -> Self {
+        let http = build_client(ProxyConfig::from_env().as_ref());
+        HubClientCredentials { hub_url, client_id, client_secret, scope, http, token: Mutex::new(None) }
+    }
+
+    /// Hub is reached through the same proxy as youtrack, `None` connects directly
+    pub fn with_proxy(mut self, proxy: Option<&ProxyConfig>) -> Self {
+        self.http = build_client(proxy);
+        self
+    }
+
+    async fn request_token(&self) -> YoutrackResult<AccessToken> {
+        let credentials = base64::encode(format!("{}:{}", self.client_id, self.client_secret));
+        let body = format!("grant_type=client_credentials&scope={}", crate::rest_api::service::encode(&self.scope));
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("{}/api/rest/oauth2/token", self.hub_url.trim_end_matches('/')))
+            .header(hyper::header::AUTHORIZATION, format!("Basic {}", credentials))
+            .header(hyper::header::CONTENT_TYPE, "application/x-www-form-urlencoded")
+            .header(hyper::header::ACCEPT, "application/json")
+            .body(Body::from(body))
+            .map_err(|error| YoutrackError::Auth(error.to_string()))?;
+
+        let requested_at = Instant::now();
+        let (parts, body) = self.http.request(request).await?.into_parts();
+        let bytes = hyper::body::to_bytes(body).await?;
+        if !parts.status.is_success() {
+            return Err(YoutrackError::Status { status: parts.status, body: String::from_utf8_lossy(&bytes).to_string() });
+        }
+        let response: TokenResponse = serde_json::from_slice(&bytes)?;
+        tracing::debug!(expires_in = response.expires_in, "hub access token received");
+        Ok(AccessToken { value: response.access_token, expires_at: requested_at + Duration::from_secs(response.expires_in) })
+    }
+}
+
+#[async_trait]
+impl AuthProvider for HubClientCredentials {
+    async fn authorization(&self) -> YoutrackResult<String> {
+        let cached = self.token.lock().unwrap().clone();
+        let token = match cached {
+            Some(token) if token.is_fresh(Instant::now()) => token,
+            _ => {
+                let token = self.request_token().await?;
+                *self.token.lock().unwrap() = Some(token.clone());
+                token
+            }
+        };
+        Ok(format!("Bearer {}", token.value))
+    }
+}
+
+impl fmt::Debug for HubClientCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HubClientCredentials")
+            .field("hub_url", &self.hub_url)
+            .field("client_id", &self.client_id)
+            .field("scope", &self.scope)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+    use crate::rest_api::auth::{AccessToken, LazyToken, PermanentToken, TokenSource};
+
+    #[test]
+    fn lazy_tokens() {
+        let path = std::env::temp_dir().join(format!("youtrack-token-{}", std::process::id()));
+        std::fs::write(&path, "perm:from-file\n").unwrap();
+        let from_file = LazyToken::new(TokenSource::File(path.clone()));
+        assert_eq!(from_file.read().unwrap(), "perm:from-file");
+        std::fs::remove_file(&path).unwrap();
+
+        let missing = LazyToken::new(TokenSource::Env("YOUTRACK_TOOLS_MISSING_TOKEN".to_string()));
+        assert!(missing.read().is_err());
+
+        assert_eq!(format!("{:?}", PermanentToken::new("perm:secret".to_string())), "PermanentToken(***)");
+    }
+
+    #[test]
+    fn token_refreshed_before_expiry() {
+        let now = Instant::now();
+        let token = AccessToken { value: "1.2.3".to_string(), expires_at: now + Duration::from_secs(3600) };
+        assert!(token.is_fresh(now));
+        assert!(!token.is_fresh(now + Duration::from_secs(3570)));
+    }
+}
@@ -12,23 +12,31 @@ pub trait BaseInfo {
 
 pub mod ops {
     use async_trait::async_trait;
+    use crate::rest_api::error::YoutrackResult;
 
     #[async_trait]
     pub trait BaseOps {
-        async fn update(&mut self) -> &mut Self;
+        async fn update(&mut self) -> YoutrackResult<&mut Self>;
         // async fn delete(&self) -> Self;
-        async fn save(&mut self) -> &mut Self;
+        async fn save(&mut self) -> YoutrackResult<&mut Self>;
     }
 }
 
 pub mod client;
+pub mod proxy;
+pub mod telemetry;
 
 pub mod wrap {
     use crate::rest_api::base::client::HttpClient;
     use std::rc::Rc;
     use std::ops::{Deref, DerefMut};
     use std::sync::Arc;
+    use std::collections::BTreeSet;
+    use serde::Serialize;
+    use serde_json::Value;
 
+    /// Keeps the last known server state (`origin`) and the locally edited copy (`inner`).
+    /// Reading goes through `inner`, so edits made via `DerefMut` are visible immediately
     #[derive(Debug)]
     pub struct ActiveRecordWrap<DTO> {
         pub origin: Arc<DTO>,
@@ -36,6 +44,14 @@ pub mod wrap {
         pub http_client: HttpClient,
     }
 
+    /// Single changed field, `path` uses serialized names, e.g. `fields[2].value.name`
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct FieldChange {
+        pub path: String,
+        pub old: Value,
+        pub new: Value,
+    }
+
     impl<DTO> ActiveRecordWrap<DTO> {
         pub fn new(http_client: HttpClient, origin: DTO) -> Self {
             let origin = Arc::new(origin);
@@ -48,13 +64,63 @@ pub mod wrap {
             self.inner = self.origin.clone();
             self
         }
+
+        /// Discards local edits
+        pub fn revert(&mut self) -> &Self {
+            self.inner = self.origin.clone();
+            self
+        }
+    }
+
+    impl<DTO> ActiveRecordWrap<DTO>
+        where DTO: Serialize {
+        pub fn is_dirty(&self) -> bool {
+            if Arc::ptr_eq(&self.origin, &self.inner) {
+                return false;
+            }
+            to_json(&*self.origin) != to_json(&*self.inner)
+        }
+
+        pub fn changes(&self) -> Vec<FieldChange> {
+            let mut changes = Vec::new();
+            if !Arc::ptr_eq(&self.origin, &self.inner) {
+                diff(String::new(), &to_json(&*self.origin), &to_json(&*self.inner), &mut changes);
+            }
+            changes
+        }
+    }
+
+    fn to_json<DTO: Serialize>(dto: &DTO) -> Value {
+        serde_json::to_value(dto).expect("DTO must be serializable to json")
+    }
+
+    fn diff(path: String, old: &Value, new: &Value, changes: &mut Vec<FieldChange>) {
+        match (old, new) {
+            (Value::Object(old_fields), Value::Object(new_fields)) => {
+                let keys: BTreeSet<&String> = old_fields.keys().chain(new_fields.keys()).collect();
+                for key in keys {
+                    let field_path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    let old_value = old_fields.get(key).unwrap_or(&Value::Null);
+                    let new_value = new_fields.get(key).unwrap_or(&Value::Null);
+                    diff(field_path, old_value, new_value, changes);
+                }
+            }
+            // arrays with another length are reported as a whole
+            (Value::Array(old_items), Value::Array(new_items)) if old_items.len() == new_items.len() => {
+                for (index, (old_item, new_item)) in old_items.iter().zip(new_items).enumerate() {
+                    diff(format!("{}[{}]", path, index), old_item, new_item, changes);
+                }
+            }
+            _ if old != new => changes.push(FieldChange { path, old: old.clone(), new: new.clone() }),
+            _ => {}
+        }
     }
 
     impl<DTO> Deref for ActiveRecordWrap<DTO> {
         type Target = DTO;
 
         fn deref(&self) -> &Self::Target {
-            &*self.origin
+            &*self.inner
         }
     }
 
@@ -64,4 +130,61 @@ pub mod wrap {
             Arc::make_mut(&mut self.inner)
         }
     }
+
+    #[cfg(test)]
+    mod tests {
+        use serde::Serialize;
+        use serde_json::json;
+        use crate::rest_api::base::client::HttpClient;
+        use crate::rest_api::base::wrap::{ActiveRecordWrap, FieldChange};
+        use crate::rest_api::client::Config;
+
+        #[derive(Serialize, Clone, Debug)]
+        struct ProjectDto {
+            name: String,
+            tags: Vec<String>,
+            leader: Option<String>,
+        }
+
+        fn wrap() -> ActiveRecordWrap<ProjectDto> {
+            let config = Config::new("http://localhost".to_string(), "token".to_string());
+            let dto = ProjectDto { name: "SSP".to_string(), tags: vec!["a".to_string()], leader: None };
+            ActiveRecordWrap::new(HttpClient::new(config), dto)
+        }
+
+        #[test]
+        fn read_your_writes() {
+            let mut project = wrap();
+            assert!(!project.is_dirty());
+
+            project.name = "SSP2".to_string();
+            assert_eq!(project.name, "SSP2");
+            assert_eq!(project.origin.name, "SSP");
+            assert!(project.is_dirty());
+        }
+
+        #[test]
+        fn changes_and_revert() {
+            let mut project = wrap();
+            project.tags[0] = "b".to_string();
+            project.leader = Some("sege".to_string());
+
+            assert_eq!(project.changes(), vec![
+                FieldChange { path: "leader".to_string(), old: json!(null), new: json!("sege") },
+                FieldChange { path: "tags[0]".to_string(), old: json!("a"), new: json!("b") },
+            ]);
+
+            project.revert();
+            assert!(!project.is_dirty());
+            assert!(project.changes().is_empty());
+            assert_eq!(project.tags[0], "a");
+        }
+
+        #[test]
+        fn same_value_is_not_dirty() {
+            let mut project = wrap();
+            project.name = "SSP".to_string();
+            assert!(!project.is_dirty());
+        }
+    }
 }
@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use hyper::{Client, Uri, Response, Body, Method};
-use hyper::client::{HttpConnector, ResponseFuture};
+use hyper::{Uri, Response, Body, Method};
+use hyper::client::ResponseFuture;
 use std::ops::Deref;
 use serde::export::fmt::Debug;
 use serde::export::Formatter;
@@ -10,8 +10,15 @@ use std::time::Instant;
 use crate::rest_api::client::Config;
 use hyper::http::HeaderValue;
 use serde::Serialize;
-
-pub type HyperClient = Client<HttpConnector, hyper::Body>;
+use hyper::body::Bytes;
+use std::future::Future;
+use tracing::Instrument;
+use tracing::field::{self, Empty};
+use crate::rest_api::base::telemetry::{self, REQUESTS_TOTAL, REQUEST_ERRORS_TOTAL, REQUEST_DURATION_SECONDS, RESPONSE_SIZE_BYTES};
+use crate::rest_api::error::{YoutrackError, YoutrackResult};
+use crate::rest_api::cache::IssueCache;
+use crate::rest_api::base::proxy::build_client;
+pub use crate::rest_api::base::proxy::HyperClient;
 
 pub struct HttpClient {
     inner: Arc<HyperClient>,
@@ -26,51 +33,147 @@ impl HttpClient {
         //     .http2_only(true)
         //     .build_http();
 
-        let default_client = Default::default();
-        let inner = Arc::new(default_client);
+        let inner = Arc::new(build_client(config.proxy.as_ref()));
         HttpClient { inner, created_at: Instant::now(), config }
     }
 
     pub fn refresh_client(&mut self) -> &Self {
-        *Arc::make_mut(&mut self.inner) = Default::default();
+        self.inner = Arc::new(build_client(self.config.proxy.as_ref()));
         self.created_at = Instant::now();
         self
     }
 
     /// Async method for getting data from the server
-    /// Uses GET method and support auth using Bearer and Auth token
-    pub async fn fetch_data(&self, path: String) -> hyper::Result<Response<Body>> {
-        log::info!("Fetching data from uri: {}", &path);
+    /// Uses GET method, `Authorization` header is taken from the auth provider of the config
+    pub async fn fetch_data(&self, path: String) -> YoutrackResult<Response<Body>> {
         let uri = self.to_uri(path);
 
-        let request = hyper::Request::builder()
-            .uri(uri)
-            .header(hyper::header::AUTHORIZATION, self.get_bearer())
-            .method(Method::GET)
-            .body(Body::empty())
-            .unwrap();
+        let request = self.request_builder(Method::GET, uri).await?
+            .body(Body::empty())?;
 
-        self.inner.request(request).await
+        Ok(self.inner.request(request).await?)
     }
 
-    pub async fn post_data<T>(&self, path: String, data: T) -> hyper::Result<Response<Body>> where T: Clone+Serialize {
+    pub async fn post_data<T>(&self, path: String, data: T) -> YoutrackResult<Response<Body>> where T: Clone+Serialize {
         let uri = self.to_uri(path);
-        log::info!("POST Request: {}", &uri);
 
-        let body = serde_json::to_string(&data).unwrap();
-        let request = hyper::Request::builder()
-            .method(Method::POST)
-            .uri(uri)
-            .header(hyper::header::AUTHORIZATION, self.get_bearer())
+        let body = serde_json::to_string(&data)?;
+        let request = self.request_builder(Method::POST, uri).await?
             .header(hyper::header::CONTENT_TYPE, "application/json")
-            .body(Body::from(body))
-            .unwrap();
+            .body(Body::from(body))?;
+
+        Ok(self.inner.request(request).await?)
+    }
+
+    /// Request with arbitrary method and already encoded body
+    pub async fn send_data(&self, method: Method, path: String, content_type: Option<String>, body: Vec<u8>) -> YoutrackResult<Response<Body>> {
+        let uri = self.to_uri(path);
+
+        let mut builder = self.request_builder(method, uri).await?;
+        if let Some(content_type) = content_type {
+            builder = builder.header(hyper::header::CONTENT_TYPE, content_type);
+        }
+        let request = builder.body(Body::from(body))?;
+
+        Ok(self.inner.request(request).await?)
+    }
+
+    /// Request with `Authorization`, `User-Agent` and the default headers of the config.
+    /// Invalid header values are reported by `body()` of the builder as `YoutrackError::Request`
+    async fn request_builder(&self, method: Method, uri: Uri) -> YoutrackResult<hyper::http::request::Builder> {
+        let mut builder = hyper::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(hyper::header::USER_AGENT, self.config.user_agent.as_str());
+        if let Some(headers) = builder.headers_mut() {
+            headers.extend(self.config.default_headers.iter().map(|(name, value)| (name.clone(), value.clone())));
+        }
+        Ok(builder.header(hyper::header::AUTHORIZATION, self.config.auth.authorization().await?))
+    }
+
+    /// GET with the whole body read, traced and measured
+    pub async fn get_bytes(&self, path: String) -> YoutrackResult<Bytes> {
+        let response = self.fetch_data(path.clone());
+        self.traced(Method::GET, &path, response).await
+    }
 
-        self.inner.request(request).await
+    /// POST with the whole body read, traced and measured
+    pub async fn post_bytes<T>(&self, path: String, data: T) -> YoutrackResult<Bytes> where T: Clone + Serialize {
+        let response = self.post_data(path.clone(), data);
+        self.traced(Method::POST, &path, response).await
     }
 
-    fn get_bearer(&self)->String {
-        format!("Bearer {}", self.config.token)
+    /// Any request with the whole body read, traced and measured
+    pub async fn send_bytes(&self, method: Method, path: String, content_type: Option<String>, body: Vec<u8>) -> YoutrackResult<Bytes> {
+        let response = self.send_data(method.clone(), path.clone(), content_type, body);
+        self.traced(method, &path, response).await
+    }
+
+    /// Uploads a file as `multipart/form-data`, used for attachments
+    pub async fn post_file(&self, path: String, file_name: &str, mime_type: &str, data: &[u8]) -> YoutrackResult<Bytes> {
+        let boundary = format!("youtrack-tools-{}", self.created_at.elapsed().as_nanos());
+        let mut body = Vec::with_capacity(data.len() + 256);
+        body.extend_from_slice(format!("--{}\r\n", boundary).as_bytes());
+        body.extend_from_slice(format!("Content-Disposition: form-data; name=\"file\"; filename=\"{}\"\r\n",
+                                       part_header_value(file_name).replace('"', "")).as_bytes());
+        body.extend_from_slice(format!("Content-Type: {}\r\n\r\n", part_header_value(mime_type)).as_bytes());
+        body.extend_from_slice(data);
+        body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+
+        let content_type = format!("multipart/form-data; boundary={}", boundary);
+        self.send_bytes(Method::POST, path, Some(content_type), body).await
+    }
+
+    async fn traced<F>(&self, method: Method, path: &str, response: F) -> YoutrackResult<Bytes>
+        where F: Future<Output=YoutrackResult<Response<Body>>> {
+        let template = telemetry::path_template(path);
+        let span = tracing::info_span!("youtrack_request",
+            method = %method, path = %template, issue_id = Empty, status = Empty, latency_ms = Empty, bytes = Empty);
+        if let Some(issue_id) = telemetry::issue_id(path) {
+            span.record("issue_id", &field::display(issue_id));
+        }
+
+        let started = Instant::now();
+        let result = async {
+            let (parts, body) = response.await?.into_parts();
+            let bytes = hyper::body::to_bytes(body).await?;
+            Ok::<_, YoutrackError>((parts.status, bytes))
+        }.instrument(span.clone()).await;
+        let latency = started.elapsed();
+        span.record("latency_ms", &(latency.as_millis() as u64));
+
+        let metrics = &self.config.metrics;
+        let mut labels = vec![("method", method.to_string()), ("path", template)];
+        match result {
+            Ok((status, bytes)) => {
+                span.record("status", &status.as_u16());
+                span.record("bytes", &(bytes.len() as u64));
+                span.in_scope(|| {
+                    tracing::debug!("request completed");
+                    tracing::trace!(body = %telemetry::redact(&String::from_utf8_lossy(&bytes)), "response body");
+                });
+
+                labels.push(("status", status.as_u16().to_string()));
+                metrics.increment_counter(REQUESTS_TOTAL, &labels);
+                metrics.record_histogram(REQUEST_DURATION_SECONDS, latency.as_secs_f64(), &labels);
+                metrics.record_histogram(RESPONSE_SIZE_BYTES, bytes.len() as f64, &labels);
+
+                if status.is_success() {
+                    Ok(bytes)
+                } else {
+                    Err(YoutrackError::Status { status, body: String::from_utf8_lossy(&bytes).to_string() })
+                }
+            }
+            Err(error) => {
+                span.in_scope(|| tracing::warn!(error = %error, "request failed"));
+                metrics.increment_counter(REQUEST_ERRORS_TOTAL, &labels);
+                Err(error)
+            }
+        }
+    }
+
+    pub fn issue_cache(&self) -> Option<&IssueCache> {
+        self.config.issue_cache.as_deref()
     }
 
     fn to_uri(&self, path: String) -> Uri {
@@ -80,6 +183,11 @@ impl HttpClient {
     }
 }
 
+/// Control characters are dropped, CR/LF in a file name would start new headers of the part
+fn part_header_value(value: &str) -> String {
+    value.chars().filter(|char| !char.is_control()).collect()
+}
+
 impl Deref for HttpClient {
     type Target = HyperClient;
 
@@ -96,3 +204,27 @@ impl Debug for HttpClient {
             .finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::base::client::{part_header_value, HttpClient};
+    use crate::rest_api::client::Config;
+    use crate::rest_api::error::YoutrackError;
+
+    #[test]
+    fn file_names_cant_add_headers() {
+        assert_eq!(part_header_value("report.pdf\r\nContent-Type: text/html"), "report.pdfContent-Type: text/html");
+        assert_eq!(part_header_value("отчёт\t2021.pdf"), "отчёт2021.pdf");
+    }
+
+    #[test]
+    fn invalid_user_agent_is_an_error() {
+        let config = Config::new("http://localhost".to_string(), "token".to_string())
+            .with_proxy(None)
+            .with_user_agent("youtrack-tools\r\nX-Injected: 1");
+        let client = HttpClient::new(config);
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let result = runtime.block_on(client.fetch_data("/api/issues/SSP-1".to_string()));
+        assert!(matches!(result, Err(YoutrackError::Request(_))), "{:?}", result);
+    }
+}
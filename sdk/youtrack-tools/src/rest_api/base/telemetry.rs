@@ -0,0 +1,76 @@
+use std::fmt::Debug;
+use regex::Regex;
+
+pub type Labels = [(&'static str, String)];
+
+pub const REQUESTS_TOTAL: &str = "youtrack_requests_total";
+pub const REQUEST_ERRORS_TOTAL: &str = "youtrack_request_errors_total";
+pub const REQUEST_DURATION_SECONDS: &str = "youtrack_request_duration_seconds";
+pub const RESPONSE_SIZE_BYTES: &str = "youtrack_response_size_bytes";
+
+/// Sink for request counters and histograms, plug in prometheus, statsd etc.
+/// Every request is labeled with `method` and `path` (template), completed ones also with `status`
+pub trait Metrics: Debug + Send + Sync {
+    fn increment_counter(&self, name: &'static str, labels: &Labels);
+    fn record_histogram(&self, name: &'static str, value: f64, labels: &Labels);
+}
+
+#[derive(Debug, Default)]
+pub struct NoopMetrics;
+
+impl Metrics for NoopMetrics {
+    fn increment_counter(&self, _name: &'static str, _labels: &Labels) {}
+
+    fn record_histogram(&self, _name: &'static str, _value: f64, _labels: &Labels) {}
+}
+
+lazy_static! {
+    static ref PERMANENT_TOKEN: Regex = Regex::new(r"perm:[A-Za-z0-9+/=._-]+").unwrap();
+    static ref BEARER_TOKEN: Regex = Regex::new(r"(?i)bearer\s+[A-Za-z0-9+/=._:-]+").unwrap();
+    static ref EMAIL: Regex = Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap();
+}
+
+/// Hides tokens and emails, used for the response bodies in trace logs
+pub fn redact(text: &str) -> String {
+    let text = PERMANENT_TOKEN.replace_all(text, "perm:***");
+    let text = BEARER_TOKEN.replace_all(&text, "Bearer ***");
+    EMAIL.replace_all(&text, "***@***").into_owned()
+}
+
+/// Path without query where every segment with digits is an identifier:
+/// `/api/issues/SSP-7/fields/107-14?fields=id` -> `/api/issues/{id}/fields/{id}`
+pub fn path_template(path: &str) -> String {
+    let path = path.split('?').next().unwrap_or_default();
+    path.split('/')
+        .map(|segment| if segment.chars().any(|c| c.is_ascii_digit()) { "{id}" } else { segment })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+pub fn issue_id(path: &str) -> Option<&str> {
+    let rest = path.strip_prefix("/api/issues/")?;
+    rest.split(&['/', '?'][..])
+        .next()
+        .filter(|id| !id.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::base::telemetry::{issue_id, path_template, redact};
+
+    #[test]
+    fn templates() {
+        assert_eq!(path_template("/api/issues/SSP-7/fields/107-14?$top=-1&fields=id"), "/api/issues/{id}/fields/{id}");
+        assert_eq!(path_template("/api/admin/customFieldSettings/bundles/state/96-1/values"),
+                   "/api/admin/customFieldSettings/bundles/state/{id}/values");
+        assert_eq!(issue_id("/api/issues/SSP-7?fields=id"), Some("SSP-7"));
+        assert_eq!(issue_id("/api/issues/2-20/fields/107-14"), Some("2-20"));
+        assert_eq!(issue_id("/api/admin/projects"), None);
+    }
+
+    #[test]
+    fn redacts_tokens_and_emails() {
+        let body = r#"{"email":"user@example.com","token":"perm:dGVzdA==.MS0x.xxxx","auth":"Bearer abc.def"}"#;
+        assert_eq!(redact(body), r#"{"email":"***@***","token":"perm:***","auth":"Bearer ***"}"#);
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use crate::rest_api::base::Ideantifier;
+use crate::rest_api::json_models::issue::IssueDto;
+
+#[derive(Clone, Debug)]
+pub struct CacheConfig {
+    pub max_size: usize,
+    /// Entries younger than TTL are returned without any request
+    pub ttl: Duration,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        CacheConfig { max_size: 256, ttl: Duration::from_secs(30) }
+    }
+}
+
+#[derive(Debug, Hash, PartialEq, Eq)]
+struct CacheKey {
+    issue_id: Ideantifier,
+    projection: String,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+    issue: IssueDto,
+    checked_at: Instant,
+}
+
+pub enum Lookup {
+    Fresh(IssueDto),
+    /// TTL is over, entry must be revalidated by the `updated` timestamp
+    Stale(IssueDto),
+    Missing,
+}
+
+/// Issues keyed by id and projection, shared by all issues of a client
+#[derive(Debug)]
+pub struct IssueCache {
+    config: CacheConfig,
+    entries: Mutex<HashMap<CacheKey, CacheEntry>>,
+}
+
+impl IssueCache {
+    pub fn new(config: CacheConfig) -> Self {
+        IssueCache { config, entries: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn get(&self, issue_id: &str, projection: &str) -> Lookup {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(&key(issue_id, projection)) {
+            Some(entry) if entry.checked_at.elapsed() < self.config.ttl => Lookup::Fresh(entry.issue.clone()),
+            Some(entry) => Lookup::Stale(entry.issue.clone()),
+            None => Lookup::Missing,
+        }
+    }
+
+    pub fn put(&self, issue_id: Ideantifier, projection: &str, issue: IssueDto) {
+        if self.config.max_size == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap();
+        let key = CacheKey { issue_id, projection: projection.to_string() };
+        if !entries.contains_key(&key) && entries.len() >= self.config.max_size {
+            let oldest = entries.iter()
+                .min_by_key(|(_, entry)| entry.checked_at)
+                .map(|(key, _)| CacheKey { issue_id: key.issue_id.clone(), projection: key.projection.clone() });
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, CacheEntry { issue, checked_at: Instant::now() });
+    }
+
+    /// Marks entry as checked now, so it is fresh for another TTL
+    pub fn touch(&self, issue_id: &str, projection: &str) {
+        if let Some(entry) = self.entries.lock().unwrap().get_mut(&key(issue_id, projection)) {
+            entry.checked_at = Instant::now();
+        }
+    }
+
+    /// Removes every projection of the issue, `issue_id` can be either id or readable id
+    pub fn invalidate(&self, issue_id: &str) {
+        self.entries.lock().unwrap().retain(|key, entry| {
+            key.issue_id != issue_id
+                && entry.issue.id != issue_id
+                && entry.issue.id_readable.as_deref() != Some(issue_id)
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn key(issue_id: &str, projection: &str) -> CacheKey {
+    CacheKey { issue_id: issue_id.to_string(), projection: projection.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::time::Duration;
+    use crate::rest_api::cache::{CacheConfig, IssueCache, Lookup};
+    use crate::rest_api::json_models::issue::IssueDto;
+
+    fn issue() -> IssueDto {
+        let file = File::open("src/rest_api/json_models/issue.json")
+            .or(File::open("sdk/youtrack-tools/src/rest_api/json_models/issue.json"))
+            .unwrap();
+        serde_json::from_reader(BufReader::new(file)).unwrap()
+    }
+
+    #[test]
+    fn fresh_and_stale() {
+        let cache = IssueCache::new(CacheConfig::default());
+        assert!(matches!(cache.get("SSP-1", "id"), Lookup::Missing));
+        cache.put("SSP-1".to_string(), "id", issue());
+        assert!(matches!(cache.get("SSP-1", "id"), Lookup::Fresh(_)));
+        assert!(matches!(cache.get("SSP-1", "id,summary"), Lookup::Missing));
+
+        let cache = IssueCache::new(CacheConfig { ttl: Duration::from_secs(0), ..CacheConfig::default() });
+        cache.put("SSP-1".to_string(), "id", issue());
+        assert!(matches!(cache.get("SSP-1", "id"), Lookup::Stale(_)));
+    }
+
+    #[test]
+    fn evicts_oldest() {
+        let cache = IssueCache::new(CacheConfig { max_size: 2, ..CacheConfig::default() });
+        cache.put("SSP-1".to_string(), "id", issue());
+        cache.put("SSP-2".to_string(), "id", issue());
+        cache.put("SSP-3".to_string(), "id", issue());
+        assert_eq!(cache.len(), 2);
+        assert!(matches!(cache.get("SSP-1", "id"), Lookup::Missing));
+        assert!(matches!(cache.get("SSP-3", "id"), Lookup::Fresh(_)));
+    }
+
+    #[test]
+    fn invalidates_by_any_id() {
+        let cache = IssueCache::new(CacheConfig::default());
+        cache.put("SSP-1".to_string(), "id", issue());
+        cache.put("SSP-1".to_string(), "id,summary", issue());
+        // fixture has id "2-20" and idReadable "SSP-1"
+        cache.invalidate("2-20");
+        assert!(cache.is_empty());
+    }
+}
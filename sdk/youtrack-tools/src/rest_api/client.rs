@@ -7,6 +7,15 @@ use hyper::client::HttpConnector;
 use tokio::time::Duration;
 use crate::rest_api::service::issues::fetch_issue_by_id;
 use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::base::telemetry::{Metrics, NoopMetrics};
+use crate::rest_api::cache::{CacheConfig, IssueCache};
+use crate::rest_api::auth::{AuthProvider, PermanentToken};
+use crate::rest_api::base::proxy::ProxyConfig;
+use hyper::HeaderMap;
+use crate::rest_api::article::Article;
+use crate::rest_api::error::YoutrackResult;
+use crate::rest_api::json_models::article::NewArticle;
+use crate::rest_api::service::articles::{fetch_article, search_articles, create_article};
 
 pub struct YoutrackClientImpl {
     client: Arc<HttpClient>,
@@ -16,7 +25,52 @@ pub struct YoutrackClientImpl {
 #[derive(Clone, Debug)]
 pub struct Config {
     pub host: String,
-    pub token: String,
+    pub auth: Arc<dyn AuthProvider>,
+    pub metrics: Arc<dyn Metrics>,
+    pub issue_cache: Option<Arc<IssueCache>>,
+    /// `HTTPS_PROXY`/`NO_PROXY` of the environment by default
+    pub proxy: Option<ProxyConfig>,
+    /// Sent with every request, e.g. headers required by the corporate gateway
+    pub default_headers: HeaderMap,
+    pub user_agent: String,
+}
+
+/// Identifies the integration in youtrack and proxy logs
+pub const DEFAULT_USER_AGENT: &str = concat!("youtrack-tools/", env!("CARGO_PKG_VERSION"));
+
+impl Config {
+    /// Config with a permanent token
+    pub fn new(host: String, token: String) -> Self {
+        Config::with_auth(host, Arc::new(PermanentToken::new(token)))
+    }
+
+    pub fn with_auth(host: String, auth: Arc<dyn AuthProvider>) -> Self {
+        Config {
+            host,
+            auth,
+            metrics: Arc::new(NoopMetrics),
+            issue_cache: None,
+            proxy: ProxyConfig::from_env(),
+            default_headers: HeaderMap::new(),
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+        }
+    }
+
+    /// `None` connects directly even if `HTTPS_PROXY` is set
+    pub fn with_proxy(mut self, proxy: Option<ProxyConfig>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: &str) -> Self {
+        self.user_agent = user_agent.to_string();
+        self
+    }
+
+    pub fn with_default_headers(mut self, headers: HeaderMap) -> Self {
+        self.default_headers = headers;
+        self
+    }
 }
 
 #[async_trait]
@@ -24,32 +78,70 @@ pub trait YoutrackClient: Sync {
     // async fn users(&self) -> Vec<Box<dyn User>>;
     // async fn user(&self, name: NameType) -> Vec<Box<dyn User>>;
     // async fn tasks(&self) -> Vec<Box<dyn Task>>;
-    async fn issue(&self, name: NameType) -> Box<Issue>;
+    async fn issue(&self, name: NameType) -> YoutrackResult<Box<Issue>>;
     // async fn projects(&self) -> Vec<Box<dyn Project>>;
     // async fn project(&self, name: NameType) -> Box<dyn Project>;
 }
 
 impl YoutrackClientImpl {
     pub async fn new(domain: String, bearer_token: String) -> Result<YoutrackClientImpl, ()> {
-        let url = "something".parse::<hyper::Uri>().unwrap();
-
-        let mut req = hyper::Request::new(url);
-        req.headers_mut().insert(hyper::header::AUTHORIZATION, "Bearer perm:token".parse().unwrap());
+        let config = Config::new(domain, bearer_token);
+        Ok(YoutrackClientImpl::from_config(config))
+    }
 
-        let config = Config { host: domain, token: bearer_token };
-        let youtrack_client = YoutrackClientImpl {
+    /// Client with any auth provider, e.g. `Config::with_auth(host, Arc::new(LazyToken::new(TokenSource::Env(..))))`
+    pub fn from_config(config: Config) -> Self {
+        YoutrackClientImpl {
             client: Arc::new(HttpClient::new(config.clone())),
             config,
-        };
-        Ok(youtrack_client)
+        }
+    }
+
+    /// Sends request counters and histograms of every issue created by this client to `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<dyn Metrics>) -> Self {
+        self.config.metrics = metrics;
+        self.client = Arc::new(HttpClient::new(self.config.clone()));
+        self
+    }
+
+    /// Shares one issue cache between all issues created by this client
+    pub fn with_cache(mut self, cache_config: CacheConfig) -> Self {
+        self.config.issue_cache = Some(Arc::new(IssueCache::new(cache_config)));
+        self.client = Arc::new(HttpClient::new(self.config.clone()));
+        self
+    }
+
+    /// Shared http client for the functions of `service` modules, e.g. admin calls
+    pub fn http_client(&self) -> &HttpClient {
+        &self.client
+    }
+
+    pub async fn article(&self, id: NameType) -> YoutrackResult<Article> {
+        let http_client = HttpClient::new(self.config.clone());
+        let origin = fetch_article(&http_client, &id).await?;
+        Ok(Article::new(http_client, origin))
+    }
+
+    /// First `top` articles matching the youtrack query
+    pub async fn search_articles(&self, query: &str, top: usize) -> YoutrackResult<Vec<Article>> {
+        let articles = search_articles(&self.client, query, 0, top).await?;
+        Ok(articles.into_iter()
+            .map(|origin| Article::new(HttpClient::new(self.config.clone()), origin))
+            .collect())
+    }
+
+    pub async fn create_article(&self, article: NewArticle) -> YoutrackResult<Article> {
+        let http_client = HttpClient::new(self.config.clone());
+        let origin = create_article(&http_client, article).await?;
+        Ok(Article::new(http_client, origin))
     }
 }
 
 #[async_trait]
 impl YoutrackClient for YoutrackClientImpl {
-    async fn issue(&self, name: NameType) -> Box<Issue> {
+    async fn issue(&self, name: NameType) -> YoutrackResult<Box<Issue>> {
         let http_client = HttpClient::new(self.config.clone());
-        let origin = fetch_issue_by_id(&http_client, name.clone()).await;
-        box Issue::new(http_client, origin)
+        let origin = fetch_issue_by_id(&http_client, name.clone()).await?;
+        Ok(box Issue::new(http_client, origin))
     }
 }
\ No newline at end of file
@@ -0,0 +1,84 @@
+use std::fmt;
+use hyper::StatusCode;
+
+pub type YoutrackResult<T> = Result<T, YoutrackError>;
+
+#[derive(Debug)]
+pub enum YoutrackError {
+    Http(hyper::Error),
+    /// Request can't be built, e.g. the user agent or a token has characters which aren't allowed in headers
+    Request(hyper::http::Error),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    /// Token can't be read or requested
+    Auth(String),
+    /// Server answered with a non-success status, body holds the youtrack error description
+    Status { status: StatusCode, body: String },
+    WrongStateName { state_name: String, available: Vec<String> },
+    /// State-machine workflow has no event leading from `from` to `to`
+    ForbiddenTransition { from: String, to: String, allowed: Vec<String> },
+    /// Issue has no State field with a state value
+    NoStateField(String),
+    /// State field of the issue isn't driven by a state-machine workflow, the state is set by name
+    NoStateMachine(String),
+    /// Project has no field with this name or the field has no values bundle
+    FieldNotFound { project: String, field: String },
+    ProjectNotFound(String),
+    /// Entity wasn't read from the server, so it can't be updated, e.g. `version v1.4.0`
+    MissingId(String),
+    /// Row of the imported file can't be turned into an issue
+    InvalidRow { row: usize, message: String },
+    /// Save failed after the issue body was posted, the server state is neither the old nor the new one
+    PartiallySaved { issue_id: String, cause: Box<YoutrackError> },
+}
+
+impl fmt::Display for YoutrackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            YoutrackError::Http(error) => write!(f, "http error: {}", error),
+            YoutrackError::Request(error) => write!(f, "request error: {}", error),
+            YoutrackError::Io(error) => write!(f, "io error: {}", error),
+            YoutrackError::Json(error) => write!(f, "json error: {}", error),
+            YoutrackError::Auth(message) => write!(f, "auth error: {}", message),
+            YoutrackError::Status { status, body } => write!(f, "unexpected status {}: {}", status, body),
+            YoutrackError::WrongStateName { state_name, available } =>
+                write!(f, r#"Wrong status name "{}". Expected values: {:?}"#, state_name, available),
+            YoutrackError::ForbiddenTransition { from, to, allowed } =>
+                write!(f, r#"Transition from "{}" to "{}" is forbidden by workflow. Allowed events: {:?}"#, from, to, allowed),
+            YoutrackError::NoStateField(issue_id) => write!(f, "Issue {} has no State field", issue_id),
+            YoutrackError::NoStateMachine(issue_id) => write!(f, "State of issue {} has no state-machine workflow", issue_id),
+            YoutrackError::FieldNotFound { project, field } =>
+                write!(f, r#"Project {} has no field "{}""#, project, field),
+            YoutrackError::ProjectNotFound(project) => write!(f, "Project {} not found", project),
+            YoutrackError::MissingId(entity) => write!(f, "{} has no id", entity),
+            YoutrackError::InvalidRow { row, message } => write!(f, "row {}: {}", row, message),
+            YoutrackError::PartiallySaved { issue_id, cause } => write!(f, "Issue {} is saved partially: {}", issue_id, cause),
+        }
+    }
+}
+
+impl std::error::Error for YoutrackError {}
+
+impl From<hyper::Error> for YoutrackError {
+    fn from(error: hyper::Error) -> Self {
+        YoutrackError::Http(error)
+    }
+}
+
+impl From<hyper::http::Error> for YoutrackError {
+    fn from(error: hyper::http::Error) -> Self {
+        YoutrackError::Request(error)
+    }
+}
+
+impl From<std::io::Error> for YoutrackError {
+    fn from(error: std::io::Error) -> Self {
+        YoutrackError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for YoutrackError {
+    fn from(error: serde_json::Error) -> Self {
+        YoutrackError::Json(error)
+    }
+}
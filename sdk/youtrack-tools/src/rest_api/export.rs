@@ -0,0 +1,164 @@
+use std::io::Write;
+use serde_json::Value;
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::error::YoutrackResult;
+use crate::rest_api::service::issues::{search_issues, ISSUE_FIELDS};
+
+/// Projection for CSV, custom field values are requested with all the names they can be resolved by
+pub const CSV_FIELDS: &str = "id,idReadable,summary,created,updated,resolved,project(shortName),reporter(login,fullName),tags(name),fields($type,name,value($type,name,fullName,login,presentation,text,minutes))";
+
+/// Columns which go before the custom fields
+const CSV_COLUMNS: [&str; 9] = ["id", "idReadable", "project", "summary", "reporter", "created", "updated", "resolved", "tags"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// One issue object per line as returned for the projection, `ISSUE_FIELDS` gives the full `IssueDto`
+    JsonLines,
+    /// One column per custom field, users, enums and states are written by their names
+    Csv,
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    pub query: String,
+    pub format: ExportFormat,
+    /// Projection, `ISSUE_FIELDS` for JSON Lines and `CSV_FIELDS` for CSV by default
+    pub fields: String,
+    pub page_size: usize,
+    /// Custom field columns of CSV, by default the fields of the first exported issue
+    pub columns: Option<Vec<String>>,
+}
+
+impl ExportOptions {
+    pub fn new(query: &str, format: ExportFormat) -> Self {
+        let fields = match format {
+            ExportFormat::JsonLines => ISSUE_FIELDS,
+            ExportFormat::Csv => CSV_FIELDS,
+        };
+        ExportOptions { query: query.to_string(), format, fields: fields.to_string(), page_size: 100, columns: None }
+    }
+}
+
+/// Writes issues page by page, so only one page is kept in memory. Returns count of the exported issues
+pub async fn export_issues<W: Write>(client: &HttpClient, options: &ExportOptions, writer: W) -> YoutrackResult<usize> {
+    let mut sink = match options.format {
+        ExportFormat::JsonLines => Sink::JsonLines(writer),
+        ExportFormat::Csv => Sink::Csv { writer: Box::new(csv::Writer::from_writer(writer)), columns: options.columns.clone(), header_written: false },
+    };
+
+    let mut exported = 0;
+    loop {
+        let page: Vec<Value> = search_issues(client, &options.query, &options.fields, exported, options.page_size).await?;
+        for issue in &page {
+            sink.write(issue)?;
+        }
+        exported += page.len();
+        tracing::debug!(exported, "issues page exported");
+        if page.len() < options.page_size {
+            break;
+        }
+    }
+    sink.flush()?;
+    Ok(exported)
+}
+
+enum Sink<W: Write> {
+    JsonLines(W),
+    Csv { writer: Box<csv::Writer<W>>, columns: Option<Vec<String>>, header_written: bool },
+}
+
+impl<W: Write> Sink<W> {
+    fn write(&mut self, issue: &Value) -> std::io::Result<()> {
+        match self {
+            Sink::JsonLines(writer) => {
+                serde_json::to_writer(&mut *writer, issue)?;
+                writer.write_all(b"\n")
+            }
+            Sink::Csv { writer, columns, header_written } => {
+                let columns = columns.get_or_insert_with(|| custom_field_names(issue));
+                if !*header_written {
+                    writer.write_record(CSV_COLUMNS.iter().map(|column| column.to_string()).chain(columns.iter().cloned()))?;
+                    *header_written = true;
+                }
+                writer.write_record(csv_record(issue, columns))?;
+                Ok(())
+            }
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Sink::JsonLines(writer) => writer.flush(),
+            Sink::Csv { writer, .. } => writer.flush(),
+        }
+    }
+}
+
+fn custom_field_names(issue: &Value) -> Vec<String> {
+    issue["fields"].as_array()
+        .map(|fields| fields.iter().filter_map(|field| field["name"].as_str()).map(String::from).collect())
+        .unwrap_or_default()
+}
+
+fn csv_record(issue: &Value, columns: &[String]) -> Vec<String> {
+    let mut record = vec![
+        display_value(&issue["id"]),
+        display_value(&issue["idReadable"]),
+        display_value(&issue["project"]),
+        display_value(&issue["summary"]),
+        display_value(&issue["reporter"]),
+        display_value(&issue["created"]),
+        display_value(&issue["updated"]),
+        display_value(&issue["resolved"]),
+        display_value(&issue["tags"]),
+    ];
+    let fields = issue["fields"].as_array();
+    for column in columns {
+        let value = fields
+            .and_then(|fields| fields.iter().find(|field| field["name"].as_str() == Some(column.as_str())))
+            .map(|field| display_value(&field["value"]))
+            .unwrap_or_default();
+        record.push(value);
+    }
+    record
+}
+
+/// Name of the value as it is shown in youtrack: full name of user, name of enum/state/version, period presentation
+fn display_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(values) => values.iter().map(display_value).collect::<Vec<_>>().join(", "),
+        Value::Object(object) => ["presentation", "fullName", "name", "login", "text", "shortName", "idReadable", "id"].iter()
+            .find_map(|key| object.get(*key).filter(|value| !value.is_null()))
+            .map(display_value)
+            .unwrap_or_default(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::export::{csv_record, custom_field_names};
+
+    #[test]
+    fn flatten_issue() {
+        let issue = serde_json::json!({
+            "id": "2-20", "idReadable": "SSP-7", "summary": "Login, then logout", "created": 1607000000000u64, "updated": null, "resolved": null,
+            "project": {"shortName": "SSP"}, "reporter": {"login": "sege", "fullName": "Sergey"}, "tags": [{"name": "backend"}, {"name": "ui"}],
+            "fields": [
+                {"$type": "StateIssueCustomField", "name": "State", "value": {"$type": "StateBundleElement", "name": "In Progress"}},
+                {"$type": "SingleUserIssueCustomField", "name": "Assignee", "value": {"$type": "User", "login": "sege", "fullName": "Sergey"}},
+                {"$type": "MultiVersionIssueCustomField", "name": "Fix versions", "value": [{"name": "v1.3.2"}, {"name": "v1.4.0"}]},
+                {"$type": "PeriodIssueCustomField", "name": "Estimation", "value": {"$type": "PeriodValue", "minutes": 90, "presentation": "1h 30m"}},
+                {"$type": "SingleEnumIssueCustomField", "name": "Priority", "value": null},
+            ],
+        });
+        let columns = custom_field_names(&issue);
+        assert_eq!(columns, vec!["State", "Assignee", "Fix versions", "Estimation", "Priority"]);
+        assert_eq!(csv_record(&issue, &columns), vec![
+            "2-20", "SSP-7", "SSP", "Login, then logout", "Sergey", "1607000000000", "", "", "backend, ui",
+            "In Progress", "Sergey", "v1.3.2, v1.4.0", "1h 30m", "",
+        ]);
+    }
+}
@@ -0,0 +1,312 @@
+use std::fmt;
+use std::io::{BufRead, Read};
+use serde_json::Value;
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::base::Ideantifier;
+use crate::rest_api::error::{YoutrackError, YoutrackResult};
+use crate::rest_api::json_models::project::ProjectRef;
+use crate::rest_api::service::admin::{fetch_project_custom_fields, find_project};
+use crate::rest_api::service::commands::{Command, execute_command, command_value};
+use crate::rest_api::service::issues::{search_issues, create_issue, update_summary, NewIssue};
+
+/// Column with the key of the issue in the legacy tracker, e.g. `gitlab-12`
+pub const KEY_COLUMN: &str = "externalKey";
+/// Comma separated in CSV, array or comma separated string in JSON Lines
+pub const TAGS_COLUMN: &str = "tags";
+
+/// Issue read from the imported file, every column except key, summary, description and tags is a custom field
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueSpec {
+    pub external_key: String,
+    pub summary: String,
+    pub description: Option<String>,
+    /// Custom field name and value name, e.g. `("Priority", "Major")`
+    pub fields: Vec<(String, String)>,
+    pub tags: Vec<String>,
+}
+
+/// Where the external key is kept in youtrack
+#[derive(Debug, Clone)]
+pub enum KeyStorage {
+    /// String field of the project, e.g. `External ID`
+    Field(String),
+    /// Tag named `{prefix}{key}`
+    Tag(String),
+}
+
+impl KeyStorage {
+    fn query(&self, project: &str, key: &str) -> String {
+        match self {
+            KeyStorage::Field(field) => format!("project: {} {{{}}}: {{{}}}", project, field, key),
+            KeyStorage::Tag(prefix) => format!("project: {} tag: {{{}{}}}", project, prefix, key),
+        }
+    }
+
+    /// Search is by words, so the found issue is checked to hold exactly this key
+    fn holds(&self, issue: &Value, key: &str) -> bool {
+        match self {
+            KeyStorage::Field(field) => issue["fields"].as_array().into_iter().flatten()
+                .any(|value| value["name"].as_str() == Some(field.as_str()) && value["value"].as_str() == Some(key)),
+            KeyStorage::Tag(prefix) => {
+                let tag = format!("{}{}", prefix, key);
+                issue["tags"].as_array().into_iter().flatten()
+                    .any(|value| value["name"].as_str() == Some(tag.as_str()))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Short name of the project, e.g. `SSP`
+    pub project: String,
+    pub key_storage: KeyStorage,
+    /// Only looks up existing issues and reports what would be created or updated
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ImportedIssue {
+    pub external_key: String,
+    /// Id of the youtrack issue, `None` for issues which would be created by dry run
+    pub issue_id: Option<Ideantifier>,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub created: Vec<ImportedIssue>,
+    pub updated: Vec<ImportedIssue>,
+    /// External key and the error, other issues are still imported
+    pub failed: Vec<(String, YoutrackError)>,
+}
+
+impl fmt::Display for ImportReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (created, updated) = if self.dry_run { ("would be created", "would be updated") } else { ("created", "updated") };
+        writeln!(f, "{} {}, {} {}, {} failed", self.created.len(), created, self.updated.len(), updated, self.failed.len())?;
+        for issue in &self.created {
+            writeln!(f, "  + {} {}", issue.external_key, issue.issue_id.as_deref().unwrap_or_default())?;
+        }
+        for issue in &self.updated {
+            writeln!(f, "  ~ {} {}", issue.external_key, issue.issue_id.as_deref().unwrap_or_default())?;
+        }
+        for (key, error) in &self.failed {
+            writeln!(f, "  ! {} {}", key, error)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn read_jsonl<R: BufRead>(reader: R) -> YoutrackResult<Vec<IssueSpec>> {
+    let mut specs = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let object: serde_json::Map<String, Value> = serde_json::from_str(&line)?;
+        let columns = object.into_iter()
+            .filter_map(|(name, value)| match value {
+                Value::Null => None,
+                Value::String(text) => Some((name, text)),
+                Value::Array(values) => Some((name, values.iter().map(json_text).collect::<Vec<_>>().join(","))),
+                other => Some((name, json_text(&other))),
+            })
+            .collect();
+        specs.push(spec_from_columns(index + 1, columns)?);
+    }
+    Ok(specs)
+}
+
+pub fn read_csv<R: Read>(reader: R) -> YoutrackResult<Vec<IssueSpec>> {
+    let mut reader = csv::Reader::from_reader(reader);
+    let headers = reader.headers().map_err(std::io::Error::from)?.clone();
+    let mut specs = Vec::new();
+    for (index, record) in reader.records().enumerate() {
+        let record = record.map_err(std::io::Error::from)?;
+        let columns = headers.iter().zip(record.iter())
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        specs.push(spec_from_columns(index + 1, columns)?);
+    }
+    Ok(specs)
+}
+
+fn json_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn spec_from_columns(row: usize, columns: Vec<(String, String)>) -> YoutrackResult<IssueSpec> {
+    let mut external_key = None;
+    let mut summary = None;
+    let mut description = None;
+    let mut fields = Vec::new();
+    let mut tags = Vec::new();
+    for (name, value) in columns {
+        match name.as_str() {
+            KEY_COLUMN => external_key = Some(value),
+            "summary" => summary = Some(value),
+            "description" => description = Some(value),
+            TAGS_COLUMN => tags = value.split(',').map(str::trim).filter(|tag| !tag.is_empty()).map(String::from).collect(),
+            _ => fields.push((name, value)),
+        }
+    }
+    let missing = |column: &str| YoutrackError::InvalidRow { row, message: format!("column {} is missing", column) };
+    Ok(IssueSpec {
+        external_key: external_key.ok_or_else(|| missing(KEY_COLUMN))?,
+        summary: summary.ok_or_else(|| missing("summary"))?,
+        description,
+        fields,
+        tags,
+    })
+}
+
+/// Creates issues with new keys and updates the issues which already hold the key.
+/// Summary and description are posted, custom fields and tags are set by one command.
+/// Issues with fields which the project doesn't have fail, with dry run as well
+pub async fn import_issues(client: &HttpClient, specs: &[IssueSpec], options: &ImportOptions) -> YoutrackResult<ImportReport> {
+    let project = find_project(client, &options.project).await?
+        .ok_or_else(|| YoutrackError::ProjectNotFound(options.project.clone()))?;
+    let project_fields: Vec<String> = fetch_project_custom_fields(client, &project.id).await?
+        .into_iter()
+        .filter_map(|field| field.field.name)
+        .collect();
+    let mut report = ImportReport { dry_run: options.dry_run, ..ImportReport::default() };
+
+    for spec in specs {
+        if let Some(field) = unknown_field(&spec.fields, &project_fields) {
+            let error = YoutrackError::FieldNotFound { project: options.project.clone(), field: field.to_string() };
+            report.failed.push((spec.external_key.clone(), error));
+            continue;
+        }
+        let existing = match find_by_key(client, options, &spec.external_key).await {
+            Ok(existing) => existing,
+            Err(error) => {
+                report.failed.push((spec.external_key.clone(), error));
+                continue;
+            }
+        };
+        let imported = match (existing, options.dry_run) {
+            (Some(issue_id), true) => Ok((false, Some(issue_id))),
+            (None, true) => Ok((true, None)),
+            (Some(issue_id), false) => update(client, spec, issue_id).await.map(|issue_id| (false, Some(issue_id))),
+            (None, false) => create(client, spec, options, &project.id).await.map(|issue_id| (true, Some(issue_id))),
+        };
+        match imported {
+            Ok((created, issue_id)) => {
+                let issue = ImportedIssue { external_key: spec.external_key.clone(), issue_id };
+                if created { report.created.push(issue) } else { report.updated.push(issue) }
+            }
+            Err(error) => {
+                tracing::warn!(key = %spec.external_key, %error, "issue wasn't imported");
+                report.failed.push((spec.external_key.clone(), error));
+            }
+        }
+    }
+    Ok(report)
+}
+
+async fn find_by_key(client: &HttpClient, options: &ImportOptions, key: &str) -> YoutrackResult<Option<Ideantifier>> {
+    let query = options.key_storage.query(&options.project, key);
+    let found: Vec<Value> = search_issues(client, &query, "id,idReadable,tags(name),fields(name,value)", 0, 10).await?;
+    Ok(found.iter()
+        .find(|issue| options.key_storage.holds(issue, key))
+        .and_then(|issue| issue["idReadable"].as_str().or_else(|| issue["id"].as_str()))
+        .map(String::from))
+}
+
+async fn create(client: &HttpClient, spec: &IssueSpec, options: &ImportOptions, project_id: &str) -> YoutrackResult<Ideantifier> {
+    let mut new_issue = NewIssue {
+        project: ProjectRef { id: project_id.to_string(), ..ProjectRef::default() },
+        summary: spec.summary.clone(),
+        description: spec.description.clone(),
+        custom_fields: Vec::new(),
+    };
+    let mut tags = spec.tags.clone();
+    match &options.key_storage {
+        KeyStorage::Field(field) => new_issue.custom_fields.push(serde_json::json!({
+            "name": field, "$type": "SimpleIssueCustomField", "value": spec.external_key,
+        })),
+        KeyStorage::Tag(prefix) => tags.push(format!("{}{}", prefix, spec.external_key)),
+    }
+    let created = create_issue(client, new_issue).await?;
+    let issue_id = created.id_readable.unwrap_or(created.id);
+    apply_fields(client, &issue_id, &spec.fields, &tags).await?;
+    Ok(issue_id)
+}
+
+async fn update(client: &HttpClient, spec: &IssueSpec, issue_id: Ideantifier) -> YoutrackResult<Ideantifier> {
+    update_summary(client, &issue_id, &spec.summary, spec.description.as_deref()).await?;
+    apply_fields(client, &issue_id, &spec.fields, &spec.tags).await?;
+    Ok(issue_id)
+}
+
+async fn apply_fields(client: &HttpClient, issue_id: &Ideantifier, fields: &[(String, String)], tags: &[String]) -> YoutrackResult<()> {
+    let query = fields_command(fields, tags);
+    if query.is_empty() {
+        return Ok(());
+    }
+    let mut command = Command::new(query, std::slice::from_ref(issue_id));
+    command.silent = true;
+    execute_command(client, command).await
+}
+
+/// First field of the row which isn't in the project, names are compared case insensitive like commands do
+fn unknown_field<'a>(fields: &'a [(String, String)], project_fields: &[String]) -> Option<&'a str> {
+    fields.iter()
+        .map(|(name, _)| name.as_str())
+        .find(|name| !project_fields.iter().any(|field| field.eq_ignore_ascii_case(name)))
+}
+
+/// `Priority Major State {In Progress} tag backend`
+fn fields_command(fields: &[(String, String)], tags: &[String]) -> String {
+    fields.iter()
+        .map(|(name, value)| format!("{} {}", name, command_value(value)))
+        .chain(tags.iter().map(|tag| format!("tag {}", command_value(tag))))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::import::{read_csv, read_jsonl, fields_command, unknown_field, KeyStorage, IssueSpec};
+
+    fn spec() -> IssueSpec {
+        IssueSpec {
+            external_key: "gitlab-12".to_string(),
+            summary: "Login fails".to_string(),
+            description: Some("Steps: open, login".to_string()),
+            fields: vec![("Priority".to_string(), "Major".to_string()), ("State".to_string(), "In Progress".to_string())],
+            tags: vec!["backend".to_string(), "legacy".to_string()],
+        }
+    }
+
+    #[test]
+    fn read_files() {
+        let csv = "externalKey,summary,description,Priority,State,tags\ngitlab-12,Login fails,\"Steps: open, login\",Major,In Progress,\"backend, legacy\"\n";
+        assert_eq!(read_csv(csv.as_bytes()).unwrap(), vec![spec()]);
+
+        let jsonl = r#"{"externalKey":"gitlab-12","summary":"Login fails","description":"Steps: open, login","Priority":"Major","State":"In Progress","tags":["backend","legacy"]}"#;
+        assert_eq!(read_jsonl(jsonl.as_bytes()).unwrap(), vec![spec()]);
+
+        assert!(read_csv("summary\nNo key\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn commands_and_queries() {
+        let spec = spec();
+        assert_eq!(fields_command(&spec.fields, &spec.tags), "Priority Major State {In Progress} tag backend tag legacy");
+        assert_eq!(unknown_field(&spec.fields, &["priority".to_string(), "State".to_string()]), None);
+        assert_eq!(unknown_field(&spec.fields, &["State".to_string()]), Some("Priority"));
+        assert_eq!(KeyStorage::Field("External ID".to_string()).query("SSP", "gitlab-12"), "project: SSP {External ID}: {gitlab-12}");
+
+        let issue = serde_json::json!({"idReadable": "SSP-7", "tags": [{"name": "gitlab-12"}], "fields": [{"name": "External ID", "value": "gitlab-120"}]});
+        assert!(KeyStorage::Tag("gitlab-".to_string()).holds(&issue, "12"));
+        assert!(!KeyStorage::Field("External ID".to_string()).holds(&issue, "gitlab-12"));
+    }
+}
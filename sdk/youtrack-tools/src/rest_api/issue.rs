@@ -2,11 +2,13 @@ use serde::{Serialize, Deserialize};
 use async_trait::async_trait;
 use crate::rest_api::base::{BaseInfo, ops::BaseOps, NameType, Ideantifier};
 use crate::rest_api::base::wrap::ActiveRecordWrap;
-use crate::rest_api::json_models::issue::IssueDto;
-use crate::rest_api::service::issues::{fetch_issue_by_id, persist_changes};
+use crate::rest_api::json_models::issue::{IssueDto, IssueWatcher};
+use crate::rest_api::service::issues::{fetch_issue_by_id, persist_changes, fetch_possible_events, apply_event};
+use crate::rest_api::error::{YoutrackError, YoutrackResult};
+use crate::rest_api::service::watchers::{fetch_watchers, set_star, set_vote, add_watchers};
 use std::ops::{Deref, DerefMut};
 use std::sync::Arc;
-use crate::rest_api::json_models::issue::field::custom_field::{IssueCustomField, IssueStatus, StateIssueCustomField};
+use crate::rest_api::json_models::issue::field::custom_field::{IssueCustomField, IssueStatus, StateIssueCustomField, StateMachine, StateMachineEvent};
 use crate::rest_api::json_models::issue::field::value::{FieldValue, StateBundleElement};
 use crate::rest_api::json_models::issue::field::IssueStateType;
 
@@ -55,54 +57,134 @@ impl BaseInfo for Issue {
 
 #[async_trait]
 impl BaseOps for Issue {
-    async fn update(&mut self) -> &mut Self {
-        let new_origin = fetch_issue_by_id(&self.http_client, self.origin.id.clone()).await;
+    async fn update(&mut self) -> YoutrackResult<&mut Self> {
+        let new_origin = fetch_issue_by_id(&self.http_client, self.origin.id.clone()).await?;
         self.refresh(new_origin);
-        self
+        Ok(self)
     }
 
-    async fn save(&mut self) -> &mut Self {
-        let new_origin = persist_changes(&self.http_client, self.origin.clone(), self.inner.clone()).await;
+    async fn save(&mut self) -> YoutrackResult<&mut Self> {
+        let new_origin = persist_changes(&self.http_client, self.origin.clone(), self.inner.clone()).await?;
         self.refresh(new_origin);
-        self
+        Ok(self)
     }
 }
 
 impl Issue {
-    pub fn set_state_name(&mut self, status_name: String) {
+    /// Local change, it's sent by `save`
+    pub fn set_state_name(&mut self, status_name: String) -> YoutrackResult<()> {
         let dto = &*self.inner;
         let new_fields = {
             let mut cloned_fields = dto.fields.clone();
+            let with_state_name = |state_custom_field: StateIssueCustomField| match state_custom_field.value.clone() {
+                FieldValue::StateBundleElement(mut state_bundle_element) => {
+                    state_bundle_element.name = Some(status_name.clone());
+                    Some(StateIssueCustomField {
+                        value: FieldValue::StateBundleElement(state_bundle_element),
+                        ..state_custom_field
+                    })
+                }
+                _ => None
+            };
             let (index, new_field) = cloned_fields.iter().enumerate()
                 .filter_map(|(index, custom_field)| {
                     match custom_field.clone() {
                         IssueCustomField::StateIssueCustomField(state_custom_field) =>
-                            match state_custom_field.value.clone() {
-                                FieldValue::StateBundleElement(mut state_bundle_element) => {
-                                    state_bundle_element.name = Some(status_name.clone());
-                                    let new_field = IssueCustomField::StateIssueCustomField(
-                                        StateIssueCustomField {
-                                            value: FieldValue::StateBundleElement(state_bundle_element),
-                                            ..state_custom_field
-                                        }
-                                    );
-                                    Some((index, new_field))
-                                }
-                                _ => None
-                            }
+                            with_state_name(state_custom_field)
+                                .map(|new_field| (index, IssueCustomField::StateIssueCustomField(new_field))),
+                        IssueCustomField::StateMachineIssueCustomField(state_custom_field) =>
+                            with_state_name(state_custom_field)
+                                .map(|new_field| (index, IssueCustomField::StateMachineIssueCustomField(new_field))),
                         _ => None
                     }
                 }).next()
-                .unwrap();
-            cloned_fields.remove(index);
-            cloned_fields.push(new_field);
+                .ok_or_else(|| YoutrackError::NoStateField(self.origin.id.clone()))?;
+            cloned_fields[index] = new_field;
             cloned_fields
         };
 
         let new_mutable_state = IssueDto { fields: new_fields, ..dto.clone() };
         *Arc::make_mut(&mut self.inner) = new_mutable_state;
+        Ok(())
     }
-    pub fn set_state(&mut self, state_type: IssueStateType) {
+    pub fn set_state(&mut self, state_type: IssueStateType) -> YoutrackResult<()> {
         self.set_state_name(state_type.into())
     }
+
+    /// Current state and events allowed by the state-machine workflow,
+    /// `None` when the State field isn't driven by a state machine
+    pub async fn state_machine(&self) -> YoutrackResult<Option<StateMachine>> {
+        let state = match self.origin.get_state() {
+            Some(state) if state.has_state_machine == Some(true) => state,
+            _ => return Ok(None)
+        };
+        let events = fetch_possible_events(&self.http_client, &self.origin.id, &state.project_custom_field.id).await?;
+        Ok(Some(StateMachine { current_state: state.state_name(), events }))
+    }
+
+    pub async fn allowed_transitions(&self) -> YoutrackResult<Vec<StateMachineEvent>> {
+        Ok(self.state_machine().await?
+            .map(|state_machine| state_machine.events)
+            .unwrap_or_default())
+    }
+
+    /// Applies workflow event to the State field and reloads the issue, local edits are discarded
+    pub async fn transition(&mut self, event: &StateMachineEvent) -> YoutrackResult<&mut Self> {
+        let state_machine = self.state_machine().await?;
+        let field_id = match (state_machine, self.origin.get_state()) {
+            (Some(state_machine), Some(state)) => {
+                if !state_machine.events.iter().any(|allowed| allowed.id == event.id) {
+                    return Err(YoutrackError::ForbiddenTransition {
+                        from: state_machine.current_state,
+                        to: event.presentation.clone(),
+                        allowed: state_machine.events.into_iter().map(|allowed| allowed.presentation).collect(),
+                    });
+                }
+                state.project_custom_field.id.clone()
+            }
+            _ => return Err(YoutrackError::NoStateMachine(self.origin.id.clone()))
+        };
+        apply_event(&self.http_client, &self.origin.id, &field_id, event).await?;
+        self.update().await
+    }
+
+    /// Users watching the issue
+    pub async fn watchers(&self) -> YoutrackResult<Vec<IssueWatcher>> {
+        Ok(fetch_watchers(&self.http_client, &self.origin.id).await?.issue_watchers)
+    }
+
+    pub async fn star(&mut self) -> YoutrackResult<&mut Self> {
+        let watchers = set_star(&self.http_client, &self.origin.id, true).await?;
+        self.update_both(|dto| dto.watchers = Some(watchers.clone()));
+        Ok(self)
+    }
+
+    pub async fn unstar(&mut self) -> YoutrackResult<&mut Self> {
+        let watchers = set_star(&self.http_client, &self.origin.id, false).await?;
+        self.update_both(|dto| dto.watchers = Some(watchers.clone()));
+        Ok(self)
+    }
+
+    pub async fn vote(&mut self) -> YoutrackResult<&mut Self> {
+        let voters = set_vote(&self.http_client, &self.origin.id, true).await?;
+        self.update_both(|dto| dto.voters = Some(voters.clone()));
+        Ok(self)
+    }
+
+    pub async fn unvote(&mut self) -> YoutrackResult<&mut Self> {
+        let voters = set_vote(&self.http_client, &self.origin.id, false).await?;
+        self.update_both(|dto| dto.voters = Some(voters.clone()));
+        Ok(self)
+    }
+
+    /// Adds other users to watchers by their logins
+    pub async fn add_watchers(&self, logins: &[String]) -> YoutrackResult<()> {
+        add_watchers(&self.http_client, &self.origin.id, logins).await
+    }
+
+    /// Applies change made on the server to both origin and local copy, so it isn't reported as a local edit
+    fn update_both<F: Fn(&mut IssueDto)>(&mut self, change: F) {
+        change(Arc::make_mut(&mut self.origin));
+        change(Arc::make_mut(&mut self.inner));
+    }
 }
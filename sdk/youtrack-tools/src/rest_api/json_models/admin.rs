@@ -0,0 +1,212 @@
+use serde::{Serialize, Deserialize};
+
+pub const CUSTOM_FIELD_FIELDS: &str = "$type,id,name,localizedName,fieldType(id),isAutoAttached,isDisplayedInIssueList,ordinal";
+pub const PROJECT_CUSTOM_FIELD_FIELDS: &str = "$type,id,field(id,name,fieldType(id)),bundle($type,id),canBeEmpty,emptyFieldText,isPublic";
+pub const BUNDLE_ELEMENT_FIELDS: &str = "$type,id,name,localizedName,description,archived,ordinal,isResolved,released,releaseDate";
+
+/// Custom field prototype from `/api/admin/customFieldSettings/customFields`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomFieldDto {
+    pub id: String,
+    pub name: String,
+    pub localized_name: Option<String>,
+    pub field_type: FieldTypeRef,
+    pub is_auto_attached: Option<bool>,
+    pub is_displayed_in_issue_list: Option<bool>,
+    pub ordinal: Option<u32>,
+}
+
+/// Type id of the field, e.g. `string`, `enum[1]`, `state[1]`, `version[*]`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldTypeRef {
+    pub id: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewCustomField {
+    pub name: String,
+    pub field_type: FieldTypeRef,
+    pub is_auto_attached: bool,
+    pub is_displayed_in_issue_list: bool,
+}
+
+impl NewCustomField {
+    pub fn new(name: &str, field_type: &str) -> Self {
+        NewCustomField {
+            name: name.to_string(),
+            field_type: FieldTypeRef { id: field_type.to_string() },
+            is_auto_attached: false,
+            is_displayed_in_issue_list: true,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomFieldRef {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field_type: Option<FieldTypeRef>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BundleRef {
+    pub id: String,
+    #[serde(rename = "$type", skip_serializing_if = "Option::is_none")]
+    pub model_type: Option<String>,
+}
+
+/// Field attached to a project, `bundle` is set for enum, state and version fields
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectCustomFieldDto {
+    #[serde(rename = "$type")]
+    pub model_type: String,
+    pub id: String,
+    pub field: CustomFieldRef,
+    pub bundle: Option<BundleRef>,
+    pub can_be_empty: Option<bool>,
+    pub empty_field_text: Option<String>,
+    pub is_public: Option<bool>,
+}
+
+/// Body for attaching a field prototype to a project
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachField {
+    #[serde(rename = "$type")]
+    pub model_type: &'static str,
+    pub field: CustomFieldRef,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bundle: Option<BundleRef>,
+    pub can_be_empty: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub empty_field_text: Option<String>,
+}
+
+impl AttachField {
+    /// String, integer, date and other fields without values bundle
+    pub fn simple(field_id: &str) -> Self {
+        AttachField {
+            model_type: "SimpleProjectCustomField",
+            field: CustomFieldRef { id: field_id.to_string(), ..CustomFieldRef::default() },
+            bundle: None,
+            can_be_empty: true,
+            empty_field_text: None,
+        }
+    }
+
+    pub fn bundled(field_id: &str, bundle_type: BundleType, bundle_id: &str) -> Self {
+        AttachField {
+            model_type: bundle_type.project_field_type(),
+            bundle: Some(BundleRef { id: bundle_id.to_string(), model_type: Some(bundle_type.bundle_type().to_string()) }),
+            ..AttachField::simple(field_id)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BundleType {
+    Enum,
+    State,
+    Version,
+}
+
+impl BundleType {
+    /// Segment of `/api/admin/customFieldSettings/bundles/{segment}`
+    pub fn path(&self) -> &'static str {
+        match self {
+            BundleType::Enum => "enum",
+            BundleType::State => "state",
+            BundleType::Version => "version",
+        }
+    }
+
+    pub fn bundle_type(&self) -> &'static str {
+        match self {
+            BundleType::Enum => "EnumBundle",
+            BundleType::State => "StateBundle",
+            BundleType::Version => "VersionBundle",
+        }
+    }
+
+    pub fn element_type(&self) -> &'static str {
+        match self {
+            BundleType::Enum => "EnumBundleElement",
+            BundleType::State => "StateBundleElement",
+            BundleType::Version => "VersionBundleElement",
+        }
+    }
+
+    pub fn project_field_type(&self) -> &'static str {
+        match self {
+            BundleType::Enum => "EnumProjectCustomField",
+            BundleType::State => "StateProjectCustomField",
+            BundleType::Version => "VersionProjectCustomField",
+        }
+    }
+}
+
+/// Value of enum, state or version bundle. `is_resolved` is used by states only,
+/// `released` and `release_date` (millis) by versions only
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleElementDto {
+    #[serde(rename = "$type", skip_serializing_if = "Option::is_none")]
+    pub model_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub localized_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub archived: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ordinal: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_resolved: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub released: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub release_date: Option<u64>,
+}
+
+impl BundleElementDto {
+    pub fn new(bundle_type: BundleType, name: &str) -> Self {
+        BundleElementDto {
+            model_type: Some(bundle_type.element_type().to_string()),
+            name: name.to_string(),
+            ..BundleElementDto::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::json_models::admin::{AttachField, BundleType, BundleElementDto};
+
+    #[test]
+    fn attach_bundled_field() {
+        let body = serde_json::to_value(AttachField::bundled("58-10", BundleType::Version, "84-2")).unwrap();
+        assert_eq!(body, serde_json::json!({
+            "$type": "VersionProjectCustomField",
+            "field": {"id": "58-10"},
+            "bundle": {"id": "84-2", "$type": "VersionBundle"},
+            "canBeEmpty": true,
+        }));
+    }
+
+    #[test]
+    fn parse_bundle_values() {
+        let values: Vec<BundleElementDto> = serde_json::from_str(include_str!("customFiledSettings.json")).unwrap();
+        assert_eq!(values.len(), 5);
+        assert_eq!(values[1].name, "In Progress");
+        assert_eq!(values[1].model_type.as_deref(), Some("StateBundleElement"));
+    }
+}
@@ -0,0 +1,91 @@
+use serde::{Serialize, Deserialize};
+use crate::rest_api::json_models::project::ProjectRef;
+
+pub const ARTICLE_FIELDS: &str = "$type,id,idReadable,summary,content,usesMarkdown,created,updated,project(id,shortName,name),parentArticle(id,idReadable,summary),childArticles(id,idReadable,summary),attachments(id,name,url,mimeType,size)";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleDto {
+    pub id: String,
+    pub id_readable: Option<String>,
+    pub summary: Option<String>,
+    /// Markdown text of the article
+    pub content: Option<String>,
+    #[serde(default)]
+    pub uses_markdown: bool,
+    pub created: Option<u64>,
+    pub updated: Option<u64>,
+    pub project: Option<ProjectRef>,
+    pub parent_article: Option<ArticleRef>,
+    #[serde(default)]
+    pub child_articles: Vec<ArticleRef>,
+    #[serde(default)]
+    pub attachments: Vec<ArticleAttachment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleRef {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_readable: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleAttachment {
+    pub id: String,
+    pub name: Option<String>,
+    pub url: Option<String>,
+    pub mime_type: Option<String>,
+    pub size: Option<u64>,
+}
+
+/// Body for creating an article, `parent_article` makes it a child (sub-article)
+#[derive(Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NewArticle {
+    pub project: ProjectRef,
+    pub summary: String,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_article: Option<ArticleRef>,
+}
+
+/// Editable part of the article, the only fields sent on save
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArticleUpdate {
+    pub summary: Option<String>,
+    pub content: Option<String>,
+    pub parent_article: Option<ArticleRef>,
+}
+
+impl ArticleUpdate {
+    pub fn new(article: &ArticleDto) -> Self {
+        let parent_article = article.parent_article.as_ref()
+            .map(|parent| ArticleRef { id: parent.id.clone(), ..ArticleRef::default() });
+        ArticleUpdate { summary: article.summary.clone(), content: article.content.clone(), parent_article }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::json_models::article::{ArticleDto, ArticleUpdate};
+
+    #[test]
+    fn parse_article() {
+        let json = r##"{"$type":"Article","id":"163-2","idReadable":"SSP-A-2","summary":"Release notes","content":"# v1.4.0","usesMarkdown":true,
+            "project":{"$type":"Project","id":"0-1","shortName":"SSP","name":"Self service portal"},
+            "parentArticle":{"$type":"Article","id":"163-1","idReadable":"SSP-A-1","summary":"Releases"},
+            "childArticles":[],"attachments":[{"$type":"ArticleAttachment","id":"164-1","name":"diagram.png","url":"/api/files/164-1","mimeType":"image/png","size":1024}]}"##;
+        let article: ArticleDto = serde_json::from_str(json).unwrap();
+        assert_eq!(article.id_readable.as_deref(), Some("SSP-A-2"));
+        assert_eq!(article.attachments[0].name.as_deref(), Some("diagram.png"));
+
+        let update = serde_json::to_value(ArticleUpdate::new(&article)).unwrap();
+        assert_eq!(update, serde_json::json!({"summary": "Release notes", "content": "# v1.4.0", "parentArticle": {"id": "163-1"}}));
+    }
+}
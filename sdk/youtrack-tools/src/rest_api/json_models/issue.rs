@@ -1,6 +1,13 @@
 use serde::{Serialize, Deserialize};
 use crate::rest_api::json_models::issue::field::{ProjectCustomFieldType, ProjectCustomField};
 use crate::rest_api::json_models::issue::field::custom_field::{IssueCustomField, StateIssueCustomField};
+use crate::rest_api::json_models::user::UserDto;
+use crate::rest_api::json_models::issue::field::value::FieldValue;
+
+/// Name of the default user field with the assignee
+pub const ASSIGNEE_FIELD: &str = "Assignee";
+/// Name of the default state field
+pub const STATE_FIELD: &str = "State";
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -10,7 +17,11 @@ pub struct IssueDto {
     pub number_in_project: Option<u8>,
     pub reporter: Option<IssueReporter>,
     pub id_readable: Option<String>,
-    // voters
+    /// Read only, votes are changed by `set_vote`
+    #[serde(default, skip_serializing)]
+    pub voters: Option<IssueVoters>,
+    #[serde(default, skip_serializing)]
+    pub votes: Option<u32>,
     pub has_email: Option<bool>,
     pub event_source_ticket: String,
     // attachments
@@ -19,17 +30,25 @@ pub struct IssueDto {
     // comments
     // externalIssue
     pub hidden_attachments_count: u8,
-    // applicableActions
+    /// Read only, actions are run as commands
+    #[serde(default, skip_serializing)]
+    pub applicable_actions: Vec<IssueAction>,
     pub is_draft: bool,
     // visibility
     pub description: Option<String>,
-    // tags
+    /// Read only, tags are changed with commands
+    #[serde(default, skip_serializing)]
+    pub tags: Vec<IssueTag>,
     // created
     // links
     // project
     pub uses_markdown: bool,
-    // updated
-    // watchers
+    /// Read only, set by the server on every change
+    #[serde(default, skip_serializing)]
+    pub updated: Option<u64>,
+    /// Read only, a posted `hasStar` would undo stars set by others after the fetch
+    #[serde(default, skip_serializing)]
+    pub watchers: Option<IssueWatchers>,
     pub fields: Vec<IssueCustomField>,
     pub id: String,
     #[serde(alias = "$type")]
@@ -40,23 +59,96 @@ impl IssueDto {
     pub fn get_state(&self) -> Option<&StateIssueCustomField> {
         self.fields.iter()
             .filter_map(|field| match field {
-                IssueCustomField::StateIssueCustomField(stateIssueCustomField) =>
+                IssueCustomField::StateIssueCustomField(stateIssueCustomField) |
+                IssueCustomField::StateMachineIssueCustomField(stateIssueCustomField) =>
                     Some(stateIssueCustomField),
                 _ => None
             })
             .next()
     }
 
+    /// Login of the user in the `Assignee` field
+    pub fn assignee_login(&self) -> Option<&str> {
+        self.fields.iter()
+            .filter_map(|field| match field {
+                IssueCustomField::SingleUserIssueCustomField(data) if data.name == ASSIGNEE_FIELD => data.value.as_ref(),
+                _ => None
+            })
+            .filter_map(|value| match value {
+                FieldValue::User(user) => user.login.as_deref(),
+                _ => None
+            })
+            .next()
+    }
+
     pub fn get_state_field(&self) -> Option<StateIssueCustomField> {
         self.fields.iter()
             .filter_map(|field| match field {
-                IssueCustomField::StateIssueCustomField(data) => Some(data.clone()),
+                IssueCustomField::StateIssueCustomField(data) |
+                IssueCustomField::StateMachineIssueCustomField(data) => Some(data.clone()),
                 _ => None
             })
             .next()
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueTag {
+    pub id: String,
+    pub name: String,
+}
+
+/// Custom field with the value as it is returned, e.g. a plain string of a string field
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RawCustomField {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "$type")]
+    pub field_type: String,
+    #[serde(default)]
+    pub value: serde_json::Value,
+}
+
+impl RawCustomField {
+    /// Name of a state, enum, version or user value, `None` for empty fields and values without a name
+    pub fn value_name(&self) -> Option<&str> {
+        self.value.get("name").and_then(serde_json::Value::as_str)
+    }
+}
+
+/// Action which can be applied to the issue by the current user, e.g. `clone`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueAction {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub executing: bool,
+}
+
+/// `has_star` is about the token user, `issue_watchers` is filled only by the watchers request
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueWatchers {
+    pub has_star: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub issue_watchers: Vec<IssueWatcher>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueWatcher {
+    pub user: UserDto,
+    pub is_starred: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueVoters {
+    pub has_vote: bool,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct IssueReporter {
@@ -69,7 +161,8 @@ pub struct IssueReporter {
 pub mod tests {
     use std::fs::File;
     use std::io::BufReader;
-    use crate::rest_api::json_models::issue::IssueDto;
+    use crate::rest_api::json_models::issue::{IssueDto, RawCustomField};
+    use crate::rest_api::json_models::issue::field::custom_field::{IssueCustomField, StateMachineFieldEvents};
 
     #[test]
     fn test_it() {
@@ -83,6 +176,60 @@ pub mod tests {
         let result: IssueDto = serde_json::from_reader(reader).unwrap();
         println!("{:?}", result)
     }
+
+    #[test]
+    fn read_only_fields_are_not_posted() {
+        let issue: IssueDto = serde_json::from_str(include_str!("issue.json")).unwrap();
+        assert!(issue.watchers.is_some() && issue.updated.is_some());
+
+        let posted = serde_json::to_value(&issue).unwrap();
+        for field in &["watchers", "voters", "votes", "applicableActions", "updated", "tags"] {
+            assert!(posted.get(field).is_none(), "{} is posted", field);
+        }
+        assert_eq!(posted["idReadable"], "SSP-1");
+    }
+
+    #[test]
+    fn state_machine_field() {
+        let json = r#"{
+            "projectCustomField": {
+                "bundle": {"id": "96-1", "$type": "StateBundle"},
+                "emptyFieldText": "No state", "isPublic": true, "ordinal": 0, "canBeEmpty": false,
+                "field": {"localizedName": null, "fieldType": {"valueType": "state", "isMultiValue": false, "$type": "FieldType"},
+                          "ordinal": 2, "name": "State", "id": "92-2", "$type": "CustomField"},
+                "id": "107-14", "$type": "StateProjectCustomField"
+            },
+            "value": {"isResolved": false, "localizedName": null, "archived": false, "color": null, "name": "Open", "id": "97-13", "$type": "StateBundleElement"},
+            "isUpdatable": true, "hasStateMachine": true, "name": "State", "id": "107-14", "$type": "StateMachineIssueCustomField"
+        }"#;
+        let field: IssueCustomField = serde_json::from_str(json).unwrap();
+        match field {
+            IssueCustomField::StateMachineIssueCustomField(state) => {
+                assert_eq!(state.has_state_machine, Some(true));
+                assert_eq!(state.state_name(), "Open");
+            }
+            other => panic!("unexpected field {:?}", other)
+        }
+
+        let events: StateMachineFieldEvents = serde_json::from_str(
+            r#"{"possibleEvents": [{"id": "start", "presentation": "Start", "$type": "Event"}], "$type": "StateMachineIssueCustomField"}"#
+        ).unwrap();
+        assert!(events.possible_events[0].matches("start"));
+        assert!(events.possible_events[0].matches("START"));
+        assert!(!events.possible_events[0].matches("fix"));
+    }
+
+    #[test]
+    fn value_names() {
+        let fields: Vec<RawCustomField> = serde_json::from_str(r#"[
+            {"$type": "StateIssueCustomField", "id": "107-14", "name": "State", "value": {"$type": "StateBundleElement", "name": "Fixed"}},
+            {"$type": "SimpleIssueCustomField", "id": "107-20", "name": "Branch", "value": "feature/SSP-7"},
+            {"$type": "SingleEnumIssueCustomField", "id": "107-21", "name": "Priority", "value": null}
+        ]"#).unwrap();
+        assert_eq!(fields[0].value_name(), Some("Fixed"));
+        assert_eq!(fields[1].value_name(), None);
+        assert_eq!(fields[2].value_name(), None);
+    }
 }
 
 pub mod field {
@@ -120,7 +267,8 @@ pub mod field {
         EnumeratedState,
         #[serde(alias = "version")]
         EnumeratedVersion,
-        // EnumeratedBuild,
+        #[serde(alias = "build")]
+        EnumeratedBuild,
         #[serde(other)]
         OtherType,
         // {
@@ -259,7 +407,7 @@ pub mod field {
         pub struct User {
             ring_id: String,
             avatar_url: Option<String>,
-            login: Option<String>,
+            pub login: Option<String>,
             full_name: Option<String>,
             name: Option<String>,
             id: Option<String>,
@@ -275,6 +423,18 @@ pub mod field {
             id: String,
         }
 
+        /// Value of a build field, `build_link` points to the CI build which produced it
+        #[serde(rename_all = "camelCase")]
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct BuildBundleElement {
+            #[serde(default)]
+            pub archived: bool,
+            pub name: Option<String>,
+            pub id: String,
+            pub build_link: Option<String>,
+            pub assemble_date: Option<u64>,
+        }
+
         #[derive(Serialize, Deserialize, Debug, Clone)]
         #[serde(tag = "$type")]
         // #[serde(tag = "$type", rename_all = "camelCase")]
@@ -283,6 +443,7 @@ pub mod field {
             StateBundleElement(StateBundleElement),
             User(User),
             VersionBundleElement(VersionBundleElement),
+            BuildBundleElement(BuildBundleElement),
             #[serde(other)]
             OtherType,
             // {
@@ -311,9 +472,9 @@ pub mod field {
         #[derive(Debug, Clone, Serialize, Deserialize)]
         pub struct SingleUserIssueCustomField {
             project_custom_field: ProjectCustomField,
-            value: Option<FieldValue>,
+            pub value: Option<FieldValue>,
             is_updatable: bool,
-            name: String,
+            pub name: String,
             id: String,
         }
 
@@ -343,10 +504,40 @@ pub mod field {
             pub project_custom_field: ProjectCustomField,
             pub value: FieldValue,
             pub is_updatable: bool,
+            /// State is changed by events of a state-machine workflow, not by setting a value
+            #[serde(skip_serializing_if = "Option::is_none")]
+            pub has_state_machine: Option<bool>,
             pub name: String,
             pub id: String,
         }
 
+        #[derive(Debug, Clone, Serialize, Deserialize)]
+        pub struct StateMachineEvent {
+            pub id: String,
+            pub presentation: String,
+        }
+
+        impl StateMachineEvent {
+            /// Event is looked up by its id or presentation, case insensitive
+            pub fn matches(&self, name: &str) -> bool {
+                self.id.eq_ignore_ascii_case(name) || self.presentation.eq_ignore_ascii_case(name)
+            }
+        }
+
+        #[serde(rename_all = "camelCase")]
+        #[derive(Debug, Clone, Deserialize)]
+        pub struct StateMachineFieldEvents {
+            #[serde(default)]
+            pub possible_events: Vec<StateMachineEvent>,
+        }
+
+        #[derive(Debug, Clone)]
+        pub struct StateMachine {
+            pub current_state: String,
+            /// Events allowed from the current state
+            pub events: Vec<StateMachineEvent>,
+        }
+
         impl StateIssueCustomField {
             pub fn field_id(&self) -> String {
                 self.project_custom_field.bundle.as_ref().unwrap()
@@ -389,6 +580,8 @@ pub mod field {
             SimpleIssueCustomField(SimpleIssueCustomField),
             #[serde(rename_all = "camelCase")]
             StateIssueCustomField(StateIssueCustomField),
+            #[serde(rename_all = "camelCase")]
+            StateMachineIssueCustomField(StateIssueCustomField),
         }
 
         pub type IssueStatus = IssueCustomField;
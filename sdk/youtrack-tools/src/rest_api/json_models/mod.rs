@@ -1,5 +1,8 @@
 pub mod issue;
 pub mod user;
+pub mod project;
+pub mod article;
+pub mod admin;
 
 mod active_exp {
     use std::ops::{Deref, DerefMut};
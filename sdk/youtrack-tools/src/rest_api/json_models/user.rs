@@ -1,3 +1,13 @@
-pub struct UserDto{
+use serde::{Serialize, Deserialize};
 
-}
\ No newline at end of file
+pub const USER_FIELDS: &str = "$type,id,login,fullName,email,ringId";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserDto {
+    pub id: String,
+    pub login: Option<String>,
+    pub full_name: Option<String>,
+    pub email: Option<String>,
+    pub ring_id: Option<String>,
+}
@@ -0,0 +1,196 @@
+use std::fmt;
+use regex::{Captures, Regex};
+
+lazy_static! {
+    static ref LINE_START_MARKER: Regex = Regex::new(r"(?m)^(\s*)([-+]|\d+\.)(\s)").unwrap();
+    static ref FENCE: Regex = Regex::new(r"(?m)^(```|~~~)").unwrap();
+    static ref INLINE_CODE: Regex = Regex::new(r"`[^`\n]*`").unwrap();
+    static ref GITLAB_REFERENCE: Regex = Regex::new(r"(^|[\s(])(!|#)(\d+)\b").unwrap();
+    static ref GITLAB_UPLOAD: Regex = Regex::new(r"\]\((/uploads/[^)\s]+)\)").unwrap();
+    static ref GITLAB_TOC: Regex = Regex::new(r"(?m)^\s*(\[\[_TOC_\]\]|\[TOC\])\s*$\n?").unwrap();
+}
+
+/// Characters which change the rendering of youtrack markdown anywhere in a line
+const SPECIAL: &[char] = &['\\', '`', '*', '_', '{', '}', '[', ']', '(', ')', '<', '>', '#', '|', '~'];
+
+/// User text which is shown as is, e.g. summary of a gitlab commit
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for char in text.chars() {
+        if SPECIAL.contains(&char) {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    }
+    // list markers only matter at the start of a line
+    LINE_START_MARKER.replace_all(&escaped, |captures: &Captures| {
+        match captures[2].strip_suffix('.') {
+            Some(number) => format!("{}{}\\.{}", &captures[1], number, &captures[3]),
+            None => format!("{}\\{}{}", &captures[1], &captures[2], &captures[3]),
+        }
+    }).to_string()
+}
+
+/// `SSP-7` is turned into a link by youtrack itself, so the id must not be escaped
+pub fn issue_mention(id_readable: &str) -> String {
+    id_readable.to_string()
+}
+
+pub fn user_mention(login: &str) -> String {
+    format!("@{}", login)
+}
+
+pub fn inline_code(code: &str) -> String {
+    let fence = "`".repeat(longest_run(code, '`') + 1);
+    if code.starts_with('`') || code.ends_with('`') {
+        format!("{} {} {}", fence, code, fence)
+    } else {
+        format!("{}{}{}", fence, code, fence)
+    }
+}
+
+/// Fence is longer than any backtick run inside, so the code can't close the block
+pub fn code_block(language: &str, code: &str) -> String {
+    let fence = "`".repeat(longest_run(code, '`').max(2) + 1);
+    format!("{}{}\n{}\n{}", fence, language, code.trim_end_matches('\n'), fence)
+}
+
+pub fn link(text: &str, url: &str) -> String {
+    format!("[{}]({})", escape(text), url.replace(' ', "%20").replace('(', "%28").replace(')', "%29"))
+}
+
+/// `[!12 Title](https://gitlab/...)`, the same form gitlab uses in its references
+pub fn merge_request_link(iid: u64, title: &str, web_url: &str) -> String {
+    link(&format!("!{} {}", iid, title), web_url)
+}
+
+pub fn table<R: AsRef<[String]>>(header: &[&str], rows: &[R]) -> String {
+    let cell = |text: &str| escape(text).replace('\n', " ");
+    let mut table = format!("| {} |\n|{}\n", header.iter().map(|name| cell(name)).collect::<Vec<_>>().join(" | "), " --- |".repeat(header.len()));
+    for row in rows {
+        let row = row.as_ref();
+        let cells = (0..header.len()).map(|index| row.get(index).map(|text| cell(text)).unwrap_or_default());
+        table.push_str(&format!("| {} |\n", cells.collect::<Vec<_>>().join(" | ")));
+    }
+    table
+}
+
+/// Gitlab flavored markdown of a merge request or a note with the references resolved,
+/// `!12` and `#34` of gitlab would point to youtrack otherwise. Code is kept as is
+pub fn from_gitlab(text: &str, project_url: &str) -> String {
+    let project_url = project_url.trim_end_matches('/');
+    let text = GITLAB_TOC.replace_all(text, "");
+    let mut converted = String::with_capacity(text.len());
+    let mut in_code_block = false;
+    for line in text.split_inclusive('\n') {
+        if FENCE.is_match(line) {
+            in_code_block = !in_code_block;
+            converted.push_str(line);
+            continue;
+        }
+        if in_code_block {
+            converted.push_str(line);
+            continue;
+        }
+        let mut last = 0;
+        for code in INLINE_CODE.find_iter(line) {
+            converted.push_str(&gitlab_references(&line[last..code.start()], project_url));
+            converted.push_str(code.as_str());
+            last = code.end();
+        }
+        converted.push_str(&gitlab_references(&line[last..], project_url));
+    }
+    converted
+}
+
+fn gitlab_references(text: &str, project_url: &str) -> String {
+    let text = GITLAB_REFERENCE.replace_all(text, |captures: &Captures| {
+        let kind = if &captures[2] == "!" { "merge_requests" } else { "issues" };
+        format!("{}[{}{}]({}/-/{}/{})", &captures[1], &captures[2], &captures[3], project_url, kind, &captures[3])
+    });
+    GITLAB_UPLOAD.replace_all(&text, |captures: &Captures| format!("]({}{})", project_url, &captures[1])).to_string()
+}
+
+fn longest_run(text: &str, char: char) -> usize {
+    text.split(|other| other != char).map(str::len).max().unwrap_or(0)
+}
+
+/// Builder of comments and descriptions, blocks are separated by an empty line
+#[derive(Debug, Default, Clone)]
+pub struct Markdown {
+    blocks: Vec<String>,
+}
+
+impl Markdown {
+    pub fn new() -> Self {
+        Markdown::default()
+    }
+
+    pub fn heading(mut self, level: usize, text: &str) -> Self {
+        self.blocks.push(format!("{} {}", "#".repeat(level.clamp(1, 6)), escape(text)));
+        self
+    }
+
+    /// Escaped text
+    pub fn text(mut self, text: &str) -> Self {
+        self.blocks.push(escape(text));
+        self
+    }
+
+    /// Already formatted markdown, e.g. built by `link` or `from_gitlab`
+    pub fn raw(mut self, markdown: &str) -> Self {
+        self.blocks.push(markdown.to_string());
+        self
+    }
+
+    pub fn list<I: IntoIterator<Item=String>>(mut self, items: I) -> Self {
+        self.blocks.push(items.into_iter().map(|item| format!("- {}", item)).collect::<Vec<_>>().join("\n"));
+        self
+    }
+
+    pub fn code_block(mut self, language: &str, code: &str) -> Self {
+        self.blocks.push(code_block(language, code));
+        self
+    }
+
+    pub fn table<R: AsRef<[String]>>(mut self, header: &[&str], rows: &[R]) -> Self {
+        self.blocks.push(table(header, rows).trim_end().to_string());
+        self
+    }
+}
+
+impl fmt::Display for Markdown {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.blocks.join("\n\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::markdown::{code_block, escape, from_gitlab, inline_code, merge_request_link, Markdown};
+
+    #[test]
+    fn escaping() {
+        assert_eq!(escape("Fix *bold* [link](x) in SSP-7"), r"Fix \*bold\* \[link\]\(x\) in SSP-7");
+        assert_eq!(escape("- not a list\n1. nor this\nwell-known 3.5"), "\\- not a list\n1\\. nor this\nwell-known 3.5");
+        assert_eq!(inline_code("a`b"), "``a`b``");
+        assert_eq!(code_block("rust", "let s = \"```\";\n"), "````rust\nlet s = \"```\";\n````");
+        assert_eq!(merge_request_link(12, "Login [WIP]", "https://git/p/-/merge_requests/12"), r"[!12 Login \[WIP\]](https://git/p/-/merge_requests/12)");
+    }
+
+    #[test]
+    fn gitlab_references() {
+        let gitlab = "[[_TOC_]]\nCloses #34, see !12 and `#5`\n![img](/uploads/ab/shot.png)\n```\n#1 !2\n```\n";
+        assert_eq!(from_gitlab(gitlab, "https://git/serge/proj2/"), "Closes [#34](https://git/serge/proj2/-/issues/34), see [!12](https://git/serge/proj2/-/merge_requests/12) and `#5`\n\
+            ![img](https://git/serge/proj2/uploads/ab/shot.png)\n```\n#1 !2\n```\n");
+    }
+
+    #[test]
+    fn builder() {
+        let comment = Markdown::new()
+            .heading(3, "Pipeline #5 failed")
+            .table(&["Job", "Status"], &[vec!["test|unit".to_string(), "failed".to_string()]])
+            .to_string();
+        assert_eq!(comment, "### Pipeline \\#5 failed\n\n| Job | Status |\n| --- | --- |\n| test\\|unit | failed |");
+    }
+}
@@ -1,8 +1,16 @@
 pub mod client;
+pub mod auth;
 mod project;
 mod user;
 pub mod base;
 pub mod issue;
+pub mod article;
+pub mod unit_of_work;
+pub mod error;
+pub mod cache;
+pub mod export;
+pub mod import;
+pub mod markdown;
 mod youtrack_const;
 pub mod json_models;
 
@@ -0,0 +1,71 @@
+use serde::de::DeserializeOwned;
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::error::YoutrackResult;
+use crate::rest_api::json_models::admin::{CustomFieldDto, NewCustomField, ProjectCustomFieldDto, AttachField, BundleType, BundleElementDto,
+                                          CUSTOM_FIELD_FIELDS, PROJECT_CUSTOM_FIELD_FIELDS, BUNDLE_ELEMENT_FIELDS};
+use crate::rest_api::json_models::project::ProjectRef;
+use crate::rest_api::service::encode;
+
+pub async fn fetch_custom_fields(client: &HttpClient) -> YoutrackResult<Vec<CustomFieldDto>> {
+    let uri = format!("/api/admin/customFieldSettings/customFields?$top=-1&fields={}", CUSTOM_FIELD_FIELDS);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn find_custom_field(client: &HttpClient, name: &str) -> YoutrackResult<Option<CustomFieldDto>> {
+    let fields = fetch_custom_fields(client).await?;
+    Ok(fields.into_iter().find(|field| field.name.eq_ignore_ascii_case(name)))
+}
+
+pub async fn create_custom_field(client: &HttpClient, field: NewCustomField) -> YoutrackResult<CustomFieldDto> {
+    let path = format!("/api/admin/customFieldSettings/customFields?fields={}", CUSTOM_FIELD_FIELDS);
+    let bytes = client.post_bytes(path, field).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn fetch_projects(client: &HttpClient, query: &str) -> YoutrackResult<Vec<ProjectRef>> {
+    let uri = format!("/api/admin/projects?query={}&$top=-1&fields=id,shortName,name", encode(query));
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Project is looked up by its short name, e.g. `SSP`
+pub async fn find_project(client: &HttpClient, short_name: &str) -> YoutrackResult<Option<ProjectRef>> {
+    let projects = fetch_projects(client, short_name).await?;
+    Ok(projects.into_iter()
+        .find(|project| matches!(project.short_name.as_deref(), Some(name) if name.eq_ignore_ascii_case(short_name))))
+}
+
+pub async fn fetch_project_custom_fields(client: &HttpClient, project_id: &str) -> YoutrackResult<Vec<ProjectCustomFieldDto>> {
+    let uri = format!("/api/admin/projects/{}/customFields?$top=-1&fields={}", project_id, PROJECT_CUSTOM_FIELD_FIELDS);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn attach_custom_field(client: &HttpClient, project_id: &str, field: AttachField) -> YoutrackResult<ProjectCustomFieldDto> {
+    let path = format!("/api/admin/projects/{}/customFields?fields={}", project_id, PROJECT_CUSTOM_FIELD_FIELDS);
+    let bytes = client.post_bytes(path, field).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Values of the bundle without archived ones, `T` is `BundleElementDto` or the issue `FieldValue`
+pub async fn fetch_bundle_values<T: DeserializeOwned>(client: &HttpClient, bundle_type: BundleType, bundle_id: &str) -> YoutrackResult<Vec<T>> {
+    let uri = format!("/api/admin/customFieldSettings/bundles/{}/{}/values?$includeArchived=false&$top=-1&fields={}",
+                      bundle_type.path(), bundle_id, BUNDLE_ELEMENT_FIELDS);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn add_bundle_value(client: &HttpClient, bundle_type: BundleType, bundle_id: &str, value: BundleElementDto) -> YoutrackResult<BundleElementDto> {
+    let path = format!("/api/admin/customFieldSettings/bundles/{}/{}/values?fields={}",
+                       bundle_type.path(), bundle_id, BUNDLE_ELEMENT_FIELDS);
+    let bytes = client.post_bytes(path, value).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn update_bundle_value(client: &HttpClient, bundle_type: BundleType, bundle_id: &str, value_id: &str, value: BundleElementDto) -> YoutrackResult<BundleElementDto> {
+    let path = format!("/api/admin/customFieldSettings/bundles/{}/{}/values/{}?fields={}",
+                       bundle_type.path(), bundle_id, value_id, BUNDLE_ELEMENT_FIELDS);
+    let bytes = client.post_bytes(path, value).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
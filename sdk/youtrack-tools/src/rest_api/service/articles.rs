@@ -0,0 +1,42 @@
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::base::Ideantifier;
+use crate::rest_api::error::YoutrackResult;
+use crate::rest_api::json_models::article::{ArticleDto, ArticleAttachment, ArticleRef, ArticleUpdate, NewArticle, ARTICLE_FIELDS};
+use crate::rest_api::service::encode;
+
+/// Searches knowledge base with the regular youtrack query, e.g. `project: SSP release notes`
+pub async fn search_articles(client: &HttpClient, query: &str, skip: usize, top: usize) -> YoutrackResult<Vec<ArticleDto>> {
+    let uri = format!("/api/articles?query={}&$skip={}&$top={}&fields={}", encode(query), skip, top, ARTICLE_FIELDS);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn fetch_article(client: &HttpClient, id: &Ideantifier) -> YoutrackResult<ArticleDto> {
+    let uri = format!("/api/articles/{}?fields={}", id, ARTICLE_FIELDS);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn create_article(client: &HttpClient, article: NewArticle) -> YoutrackResult<ArticleDto> {
+    let path = format!("/api/articles?fields={}", ARTICLE_FIELDS);
+    let bytes = client.post_bytes(path, article).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn update_article(client: &HttpClient, id: &Ideantifier, update: ArticleUpdate) -> YoutrackResult<ArticleDto> {
+    let path = format!("/api/articles/{}?fields={}", id, ARTICLE_FIELDS);
+    let bytes = client.post_bytes(path, update).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn fetch_child_articles(client: &HttpClient, id: &Ideantifier) -> YoutrackResult<Vec<ArticleRef>> {
+    let uri = format!("/api/articles/{}/childArticles?fields=id,idReadable,summary", id);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn attach_file(client: &HttpClient, id: &Ideantifier, file_name: &str, mime_type: &str, data: &[u8]) -> YoutrackResult<Vec<ArticleAttachment>> {
+    let path = format!("/api/articles/{}/attachments?fields=id,name,url,mimeType,size", id);
+    let bytes = client.post_file(path, file_name, mime_type, data).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
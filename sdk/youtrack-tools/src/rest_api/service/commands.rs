@@ -0,0 +1,83 @@
+use serde::Serialize;
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::base::Ideantifier;
+use crate::rest_api::error::YoutrackResult;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueRef {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Ideantifier>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id_readable: Option<String>,
+}
+
+impl IssueRef {
+    /// Accepts both database ids (`2-20`) and readable ids (`SSP-7`)
+    pub fn new(issue_id: &str) -> Self {
+        let is_database_id = issue_id.split('-')
+            .next()
+            .map(|prefix| !prefix.is_empty() && prefix.chars().all(|c| c.is_ascii_digit()))
+            .unwrap_or(false);
+        if is_database_id {
+            IssueRef { id: Some(issue_id.to_string()), id_readable: None }
+        } else {
+            IssueRef { id: None, id_readable: Some(issue_id.to_string()) }
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Command {
+    pub query: String,
+    pub issues: Vec<IssueRef>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Don't notify watchers about the changes
+    pub silent: bool,
+}
+
+impl Command {
+    pub fn new(query: String, issue_ids: &[Ideantifier]) -> Self {
+        let issues = issue_ids.iter().map(|issue_id| IssueRef::new(issue_id)).collect();
+        Command { query, issues, comment: None, silent: false }
+    }
+}
+
+/// Applies command the same way as the "Apply command" dialog, e.g. `State Fixed`, `tag Build-Failed`
+#[tracing::instrument(skip(client), fields(query = %command.query))]
+pub async fn execute_command(client: &HttpClient, command: Command) -> YoutrackResult<()> {
+    client.post_bytes("/api/commands?fields=issues(id,idReadable),query".to_string(), command).await?;
+    Ok(())
+}
+
+/// Values with spaces are wrapped with braces in commands and queries
+pub fn command_value(value: &str) -> String {
+    if value.contains(char::is_whitespace) {
+        format!("{{{}}}", value)
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::service::commands::{Command, command_value};
+
+    #[test]
+    fn issue_refs() {
+        let command = Command::new("star sege".to_string(), &["2-20".to_string(), "SSP-7".to_string()]);
+        assert_eq!(serde_json::to_value(&command).unwrap(), serde_json::json!({
+            "query": "star sege",
+            "issues": [{"id": "2-20"}, {"idReadable": "SSP-7"}],
+            "silent": false,
+        }));
+    }
+
+    #[test]
+    fn command_values() {
+        assert_eq!(command_value("v1.4.0"), "v1.4.0");
+        assert_eq!(command_value("Sprint 4"), "{Sprint 4}");
+    }
+}
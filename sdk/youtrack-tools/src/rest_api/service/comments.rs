@@ -0,0 +1,36 @@
+use hyper::Method;
+use serde::{Deserialize, Serialize};
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::error::YoutrackResult;
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+struct NewComment<'a> {
+    text: &'a str,
+    uses_markdown: bool,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct CommentKey {
+    pub id: String,
+}
+
+/// Adds a markdown comment on behalf of the token user
+pub async fn add_comment(client: &HttpClient, issue_id: &str, text: &str) -> YoutrackResult<CommentKey> {
+    let path = format!("/api/issues/{}/comments?fields=id", issue_id);
+    let bytes = client.post_bytes(path, NewComment { text, uses_markdown: true }).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Replaces the text, the comment stays in its place of the issue history
+pub async fn update_comment(client: &HttpClient, issue_id: &str, comment_id: &str, text: &str) -> YoutrackResult<()> {
+    let path = format!("/api/issues/{}/comments/{}?fields=id", issue_id, comment_id);
+    client.post_bytes(path, NewComment { text, uses_markdown: true }).await?;
+    Ok(())
+}
+
+pub async fn delete_comment(client: &HttpClient, issue_id: &str, comment_id: &str) -> YoutrackResult<()> {
+    let path = format!("/api/issues/{}/comments/{}", issue_id, comment_id);
+    client.send_bytes(Method::DELETE, path, None, Vec::new()).await?;
+    Ok(())
+}
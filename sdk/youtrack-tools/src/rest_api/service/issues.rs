@@ -1,111 +1,278 @@
 use crate::rest_api::base::client::HttpClient;
 use crate::rest_api::base::Ideantifier;
 use crate::rest_api::json_models::user::UserDto;
-use crate::rest_api::json_models::issue::IssueDto;
+use crate::rest_api::json_models::issue::{IssueDto, RawCustomField};
 use hyper::Uri;
 use hyper::http::uri;
 use std::sync::Arc;
 use std::ops::Deref;
 use crate::rest_api::json_models::issue::field::{IssueStateType};
 use crate::rest_api::json_models::issue::field::value::{FieldValue, StateBundleElement};
-use hyper::body::Buf;
-
-pub async fn fetch_issue_by_id(client: &HttpClient, id: Ideantifier) -> IssueDto {
-    let uri = format!("/api/issues/{}?$top=-1&$topLinks=0&fields=$type,applicableActions(description,executing,id,name),attachments($type,author(fullName,id,ringId),comment(id),created,id,imageDimensions(height,width),issue(id,project(id,ringId)),mimeType,name,removed,size,thumbnailURL,url,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId))),comments(attachments($type,author(fullName,id,ringId),comment(id),created,id,imageDimensions(height,width),issue(id,project(id,ringId)),mimeType,name,removed,size,thumbnailURL,url,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId))),id),created,description,eventSourceTicket,externalIssue(key,name,url),fields($type,hasStateMachine,id,isUpdatable,name,projectCustomField($type,bundle(id),canBeEmpty,emptyFieldText,field(fieldType(isMultiValue,valueType),id,localizedName,name,ordinal),id,isEstimation,isPublic,isSpentTime,ordinal,size),value($type,archived,avatarUrl,buildLink,color(id),fullName,id,isResolved,localizedName,login,minutes,name,presentation,ringId,text)),hasEmail,hiddenAttachmentsCount,id,idReadable,isDraft,links(direction,id,issuesSize,linkType(aggregation,directed,localizedName,localizedSourceToTarget,localizedTargetToSource,name,sourceToTarget,targetToSource,uid),trimmedIssues($type,comments($type),created,id,idReadable,isDraft,numberInProject,project(id,ringId),reporter(id),resolved,summary,voters(hasVote),votes,watchers(hasStar)),unresolvedIssuesSize),numberInProject,project($type,id,isDemo,leader(id),name,plugins(timeTrackingSettings(enabled,estimate(field(id,name),id),timeSpent(field(id,name),id)),vcsIntegrationSettings(processors(enabled,migrationFailed,server(enabled,url),upsourceHubResourceKey,url))),ringId,shortName),reporter($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),resolved,summary,tags(color(id),id,isUpdatable,isUsable,name,owner(id),query),updated,updater($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),usesMarkdown,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId)),voters(hasVote),votes,watchers(hasStar),wikifiedDescription",
-                      id);
-    let (status, body) = client.fetch_data(uri).await.unwrap().into_parts();
-    let issue_dto: IssueDto = hyper::body::to_bytes(body).await
-        .map(|bytes| {
-            log::info!("fetched issue by id: {}", String::from_utf8_lossy(bytes.bytes()));
-            serde_json::from_slice(&bytes)
-        })
-        .unwrap()
-        .unwrap();
+use crate::rest_api::json_models::issue::field::custom_field::{StateIssueCustomField, StateMachineEvent, StateMachineFieldEvents};
+use crate::rest_api::error::{YoutrackError, YoutrackResult};
+use crate::rest_api::cache::Lookup;
+use crate::rest_api::json_models::admin::BundleType;
+use crate::rest_api::service::admin::fetch_bundle_values;
+use crate::rest_api::service::encode;
+use crate::rest_api::json_models::project::ProjectRef;
+use serde::{Serialize, Deserialize};
+use serde::de::DeserializeOwned;
+
+/// Projection used for reading and saving issues
+pub const ISSUE_FIELDS: &str = "$type,applicableActions(description,executing,id,name),attachments($type,author(fullName,id,ringId),comment(id),created,id,imageDimensions(height,width),issue(id,project(id,ringId)),mimeType,name,removed,size,thumbnailURL,url,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId))),comments(attachments($type,author(fullName,id,ringId),comment(id),created,id,imageDimensions(height,width),issue(id,project(id,ringId)),mimeType,name,removed,size,thumbnailURL,url,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId))),id),created,description,eventSourceTicket,externalIssue(key,name,url),fields($type,hasStateMachine,id,isUpdatable,name,projectCustomField($type,bundle(id),canBeEmpty,emptyFieldText,field(fieldType(isMultiValue,valueType),id,localizedName,name,ordinal),id,isEstimation,isPublic,isSpentTime,ordinal,size),value($type,archived,avatarUrl,buildLink,color(id),fullName,id,isResolved,localizedName,login,minutes,name,presentation,ringId,text)),hasEmail,hiddenAttachmentsCount,id,idReadable,isDraft,links(direction,id,issuesSize,linkType(aggregation,directed,localizedName,localizedSourceToTarget,localizedTargetToSource,name,sourceToTarget,targetToSource,uid),trimmedIssues($type,comments($type),created,id,idReadable,isDraft,numberInProject,project(id,ringId),reporter(id),resolved,summary,voters(hasVote),votes,watchers(hasStar)),unresolvedIssuesSize),numberInProject,project($type,id,isDemo,leader(id),name,plugins(timeTrackingSettings(enabled,estimate(field(id,name),id),timeSpent(field(id,name),id)),vcsIntegrationSettings(processors(enabled,migrationFailed,server(enabled,url),upsourceHubResourceKey,url))),ringId,shortName),reporter($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),resolved,summary,tags(color(id),id,isUpdatable,isUsable,name,owner(id),query),updated,updater($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),usesMarkdown,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId)),voters(hasVote),votes,watchers(hasStar),wikifiedDescription";
+
+/// Uses the issue cache of the client when it is configured:
+/// fresh entries are returned as is, entries older than TTL are revalidated by the `updated` timestamp
+#[tracing::instrument(skip(client))]
+pub async fn fetch_issue_by_id(client: &HttpClient, id: Ideantifier) -> YoutrackResult<IssueDto> {
+    let cache = match client.issue_cache() {
+        Some(cache) => cache,
+        None => return fetch_issue(client, id).await,
+    };
+
+    match cache.get(&id, ISSUE_FIELDS) {
+        Lookup::Fresh(issue_dto) => {
+            tracing::debug!("issue cache hit");
+            Ok(issue_dto)
+        }
+        Lookup::Stale(issue_dto) if issue_dto.updated.is_some() && fetch_updated(client, &id).await == issue_dto.updated => {
+            tracing::debug!("issue cache entry revalidated");
+            cache.touch(&id, ISSUE_FIELDS);
+            Ok(issue_dto)
+        }
+        _ => {
+            let issue_dto = fetch_issue(client, id.clone()).await?;
+            cache.put(id, ISSUE_FIELDS, issue_dto.clone());
+            Ok(issue_dto)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct IssueUpdated {
+    updated: Option<u64>,
+}
+
+async fn fetch_updated(client: &HttpClient, id: &Ideantifier) -> Option<u64> {
+    let uri = format!("/api/issues/{}?fields=updated", id);
+    let bytes = client.get_bytes(uri).await.ok()?;
+    let issue_updated: IssueUpdated = serde_json::from_slice(&bytes).ok()?;
+    issue_updated.updated
+}
+
+async fn fetch_issue(client: &HttpClient, id: Ideantifier) -> YoutrackResult<IssueDto> {
+    let uri = format!("/api/issues/{}?$top=-1&$topLinks=0&fields={}", id, ISSUE_FIELDS);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// One page of the issues found by the youtrack query, `T` is `IssueDto` for `ISSUE_FIELDS`
+/// or `serde_json::Value` for an arbitrary projection
+pub async fn search_issues<T: DeserializeOwned>(client: &HttpClient, query: &str, fields: &str, skip: usize, top: usize) -> YoutrackResult<Vec<T>> {
+    let uri = format!("/api/issues?query={}&$skip={}&$top={}&fields={}", encode(query), skip, top, fields);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewIssue {
+    pub project: ProjectRef,
+    pub summary: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Raw field values, e.g. `{"name": "External ID", "$type": "SimpleIssueCustomField", "value": "gitlab-12"}`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub custom_fields: Vec<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IssueKey {
+    pub id: Ideantifier,
+    pub id_readable: Option<String>,
+}
+
+/// Body of `update_summary`, missing description isn't sent and stays as is
+#[derive(Serialize, Debug, Clone)]
+struct SummaryUpdate<'a> {
+    summary: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+}
+
+pub async fn create_issue(client: &HttpClient, issue: NewIssue) -> YoutrackResult<IssueKey> {
+    let bytes = client.post_bytes("/api/issues?fields=id,idReadable".to_string(), issue).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Updates only summary and description, custom fields are set by commands or `persist_changes`.
+/// `None` keeps the description
+pub async fn update_summary(client: &HttpClient, id: &Ideantifier, summary: &str, description: Option<&str>) -> YoutrackResult<()> {
+    let path = format!("/api/issues/{}?fields=id,idReadable", id);
+    let bytes = client.post_bytes(path, SummaryUpdate { summary, description }).await?;
+    invalidate_cached(client, &serde_json::from_slice(&bytes)?);
+    Ok(())
+}
 
-    issue_dto
+/// All custom fields of the issue, unlike `IssueDto` values of any type are read
+pub async fn fetch_custom_fields(client: &HttpClient, issue_id: &str) -> YoutrackResult<Vec<RawCustomField>> {
+    let path = format!("/api/issues/{}/customFields?fields=$type,id,name,value($type,id,name,login,text,presentation,buildLink)", issue_id);
+    let bytes = client.get_bytes(path).await?;
+    Ok(serde_json::from_slice(&bytes)?)
 }
 
-pub async fn persist_changes(client: &HttpClient, origin_dto: Arc<IssueDto>, modified_dto: Arc<IssueDto>) -> IssueDto {
+/// Sets a string (or any other simple) field, `None` clears it
+pub async fn set_simple_field(client: &HttpClient, issue_id: &str, field: &RawCustomField, value: Option<&str>) -> YoutrackResult<()> {
+    let path = format!("/api/issues/{}?fields=id,idReadable", issue_id);
+    let body = serde_json::json!({ "customFields": [{ "$type": field.field_type, "id": field.id, "value": value }] });
+    let bytes = client.post_bytes(path, body).await?;
+    invalidate_cached(client, &serde_json::from_slice(&bytes)?);
+    Ok(())
+}
+
+/// Issue is cached under the id it was requested with, entries of both ids are dropped
+fn invalidate_cached(client: &HttpClient, key: &IssueKey) {
+    if let Some(cache) = client.issue_cache() {
+        cache.invalidate(&key.id);
+        if let Some(id_readable) = &key.id_readable {
+            cache.invalidate(id_readable);
+        }
+    }
+}
+
+/// Posts issue body and, when it was changed, the State field.
+/// These are two independent requests, an error after the body was posted is `YoutrackError::PartiallySaved`,
+/// other errors mean nothing was changed on the server.
+/// The new state is checked before any request: against the state bundle values, or against possible events
+/// when the State field is driven by a state-machine workflow. Returns the issue after the last request
+#[tracing::instrument(skip(client, origin_dto, modified_dto), fields(issue_id = %origin_dto.id))]
+pub async fn persist_changes(client: &HttpClient, origin_dto: Arc<IssueDto>, modified_dto: Arc<IssueDto>) -> YoutrackResult<IssueDto> {
     let origin_dto = origin_dto.deref();
     let modified_dto = modified_dto.deref();
 
-    let mut issue_id = origin_dto.id.clone();
-    let issue_dto: IssueDto;
+    let issue_id = origin_dto.id.clone();
 
-    {
-        let path = format!("/api/issues/{}?$top=-1&$topLinks=0&fields=$type,applicableActions(description,executing,id,name),attachments($type,author(fullName,id,ringId),comment(id),created,id,imageDimensions(height,width),issue(id,project(id,ringId)),mimeType,name,removed,size,thumbnailURL,url,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId))),comments(attachments($type,author(fullName,id,ringId),comment(id),created,id,imageDimensions(height,width),issue(id,project(id,ringId)),mimeType,name,removed,size,thumbnailURL,url,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId))),id),created,description,eventSourceTicket,externalIssue(key,name,url),fields($type,hasStateMachine,id,isUpdatable,name,projectCustomField($type,bundle(id),canBeEmpty,emptyFieldText,field(fieldType(isMultiValue,valueType),id,localizedName,name,ordinal),id,isEstimation,isPublic,isSpentTime,ordinal,size),value($type,archived,avatarUrl,buildLink,color(id),fullName,id,isResolved,localizedName,login,minutes,name,presentation,ringId,text)),hasEmail,hiddenAttachmentsCount,id,idReadable,isDraft,links(direction,id,issuesSize,linkType(aggregation,directed,localizedName,localizedSourceToTarget,localizedTargetToSource,name,sourceToTarget,targetToSource,uid),trimmedIssues($type,comments($type),created,id,idReadable,isDraft,numberInProject,project(id,ringId),reporter(id),resolved,summary,voters(hasVote),votes,watchers(hasStar)),unresolvedIssuesSize),numberInProject,project($type,id,isDemo,leader(id),name,plugins(timeTrackingSettings(enabled,estimate(field(id,name),id),timeSpent(field(id,name),id)),vcsIntegrationSettings(processors(enabled,migrationFailed,server(enabled,url),upsourceHubResourceKey,url))),ringId,shortName),reporter($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),resolved,summary,tags(color(id),id,isUpdatable,isUsable,name,owner(id),query),updated,updater($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),usesMarkdown,visibility($type,implicitPermittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId),permittedGroups($type,allUsersGroup,icon,id,name,ringId),permittedUsers($type,avatarUrl,email,fullName,id,isLocked,issueRelatedGroup(icon),login,name,online,profiles(general(trackOnlineStatus)),ringId)),voters(hasVote),votes,watchers(hasStar),wikifiedDescription",
-                           origin_dto.id);
-        let (status, body) = client.post_data(path.to_string(), modified_dto).await.unwrap().into_parts();
+    // Logic for issue state updating
+    let state_update = match (origin_dto.get_state(), modified_dto.get_state()) {
+        (Some(origin_state), Some(modified_state)) if origin_state.state_name() != modified_state.state_name() =>
+            Some(prepare_state_update(client, &issue_id, origin_state, modified_state.state_name()).await?),
+        _ => None
+    };
 
-        use std::str;
-        issue_dto = hyper::body::to_bytes(body).await
-            .map(|bytes| {
-                let x = str::from_utf8(&bytes).unwrap();
-                println!("{}", x);
-                serde_json::from_slice(&bytes)
-            })
-            .unwrap()
-            .unwrap();
-    }
-    /// Logic for issue state updating
-    if let (Some(origin_state), Some(modified_state)) = (origin_dto.get_state(), modified_dto.get_state()) {
-        if origin_state.state_name() != modified_state.state_name() {
-            let origin_issue_id = issue_id.clone();
-
-            let (_, body) = {
-                let uri = format!("/api/admin/customFieldSettings/bundles/state/{}/values?$includeArchived=false&$skip=0&$top=50&fields=$type,name,id,localizedName,isResolved,archived,ordinal",
-                                  origin_state.field_id());
-                log::info!("GET Request: {}", &uri);
-                client.fetch_data(uri).await.unwrap()
-                    .into_parts()
-            };
-
-            let mut field_values: Vec<FieldValue> = hyper::body::to_bytes(body).await
-                .map(|bytes| {
-                    log::info!("fetched response: {}", String::from_utf8_lossy(bytes.bytes()));
-                    serde_json::from_slice(&bytes)
-                })
-                .unwrap()
-                .unwrap();
-
-            let expected_state_name = modified_state.state_name();
-
-            let new_state_value = {
-                let mut availizble_state_names = Vec::with_capacity(field_values.len());
-
-                let new_value = field_values.iter()
-                    .filter_map(|field_value| match field_value.clone() {
-                        FieldValue::StateBundleElement(
-                            StateBundleElement {
-                                name: Some(new_state_name),
-                                id: new_state_id,
-                                ..
-                            }) => {
-                            availizble_state_names.push(new_state_name.clone());
-                            if new_state_name.eq_ignore_ascii_case(&expected_state_name) {
-                                Some({
-                                    field_value.clone()
-                                })
-                            } else {
-                                None
-                            }
-                        }
-                        _ => None
-                    })
-                    .next()
-                    .expect(format!(r#"Wrong status name "{status_name}". Expected values: {values:?}"#, status_name = expected_state_name, values = availizble_state_names).as_str());
-                let mut state_custom_field = origin_state.clone();
-                state_custom_field.value = new_value;
-                state_custom_field
-            };
-
-            let path = format!("/api/issues/{issue_id}/fields/{field_id}?$top=-1&fields=$type,id,value($type,archived,avatarUrl,buildLink,color(id),fullName,id,isResolved,localizedName,login,markdownText,minutes,name,presentation,ringId,text)",
-                               issue_id = origin_issue_id, field_id = origin_state.project_custom_field.id);
-            let (_, body) = client.post_data(path, new_state_value).await.unwrap().into_parts();
-            hyper::body::to_bytes(body).await
-                .map(|bytes| log::info!("fetched response: {}", String::from_utf8_lossy(bytes.bytes())));
+    let path = format!("/api/issues/{}?$top=-1&$topLinks=0&fields={}", origin_dto.id, ISSUE_FIELDS);
+    let bytes = client.post_bytes(path, modified_dto).await?;
+    invalidate_cached(client, &IssueKey { id: origin_dto.id.clone(), id_readable: origin_dto.id_readable.clone() });
+
+    let saved = async {
+        let issue_dto: IssueDto = serde_json::from_slice(&bytes)?;
+        match state_update {
+            Some(StateUpdate::Value(new_state_value)) => {
+                let path = format!("/api/issues/{issue_id}/fields/{field_id}?$top=-1&fields=$type,id,value($type,archived,avatarUrl,buildLink,color(id),fullName,id,isResolved,localizedName,login,markdownText,minutes,name,presentation,ringId,text)",
+                                   issue_id = issue_id, field_id = new_state_value.project_custom_field.id);
+                client.post_bytes(path, new_state_value).await?;
+            }
+            Some(StateUpdate::Event { field_id, event }) => apply_event(client, &issue_id, &field_id, &event).await?,
+            None => return Ok(issue_dto),
         }
+        // the body response has the old state, workflows of the transition can change other fields as well
+        fetch_issue(client, issue_id.clone()).await
+    }.await;
+    saved.map_err(|cause| YoutrackError::PartiallySaved { issue_id: issue_id.clone(), cause: Box::new(cause) })
+}
+
+enum StateUpdate {
+    Value(Box<StateIssueCustomField>),
+    Event { field_id: String, event: StateMachineEvent },
+}
+
+async fn prepare_state_update(client: &HttpClient, issue_id: &Ideantifier, origin_state: &StateIssueCustomField, expected_state_name: String) -> YoutrackResult<StateUpdate> {
+    if origin_state.has_state_machine == Some(true) {
+        let field_id = origin_state.project_custom_field.id.clone();
+        let events = fetch_possible_events(client, issue_id, &field_id).await?;
+        return events.iter()
+            .find(|event| event.matches(&expected_state_name))
+            .cloned()
+            .map(|event| StateUpdate::Event { field_id, event })
+            .ok_or_else(|| YoutrackError::ForbiddenTransition {
+                from: origin_state.state_name(),
+                to: expected_state_name.clone(),
+                allowed: events.iter().map(|event| event.presentation.clone()).collect(),
+            });
     }
 
-    issue_dto
-}
\ No newline at end of file
+    let field_values: Vec<FieldValue> = fetch_bundle_values(client, BundleType::State, &origin_state.field_id()).await?;
+
+    let new_state_value = {
+        let mut availizble_state_names = Vec::with_capacity(field_values.len());
+
+        let new_value = field_values.iter()
+            .filter_map(|field_value| match field_value.clone() {
+                FieldValue::StateBundleElement(
+                    StateBundleElement {
+                        name: Some(new_state_name),
+                        ..
+                    }) => {
+                    availizble_state_names.push(new_state_name.clone());
+                    if new_state_name.eq_ignore_ascii_case(&expected_state_name) {
+                        Some({
+                            field_value.clone()
+                        })
+                    } else {
+                        None
+                    }
+                }
+                _ => None
+            })
+            .next()
+            .ok_or_else(|| YoutrackError::WrongStateName {
+                state_name: expected_state_name.clone(),
+                available: availizble_state_names.clone(),
+            })?;
+        let mut state_custom_field = origin_state.clone();
+        state_custom_field.value = new_value;
+        state_custom_field
+    };
+    Ok(StateUpdate::Value(Box::new(new_state_value)))
+}
+
+/// Events of the state-machine workflow which can be applied to the issue right now
+pub async fn fetch_possible_events(client: &HttpClient, issue_id: &Ideantifier, field_id: &str) -> YoutrackResult<Vec<StateMachineEvent>> {
+    let uri = format!("/api/issues/{}/fields/{}?fields=$type,possibleEvents(id,presentation)", issue_id, field_id);
+    let bytes = client.get_bytes(uri).await?;
+    let field: StateMachineFieldEvents = serde_json::from_slice(&bytes)?;
+    Ok(field.possible_events)
+}
+
+/// Names of the values of the State field of the issue, `NoStateField` when the issue has none
+pub async fn fetch_state_names(client: &HttpClient, issue_id: &Ideantifier) -> YoutrackResult<Vec<String>> {
+    let issue = fetch_issue_by_id(client, issue_id.clone()).await?;
+    let state = issue.get_state().ok_or_else(|| YoutrackError::NoStateField(issue_id.clone()))?;
+    let field_values: Vec<FieldValue> = fetch_bundle_values(client, BundleType::State, &state.field_id()).await?;
+    Ok(field_values.into_iter()
+        .filter_map(|field_value| match field_value {
+            FieldValue::StateBundleElement(StateBundleElement { name, .. }) => name,
+            _ => None
+        })
+        .collect())
+}
+
+pub async fn apply_event(client: &HttpClient, issue_id: &Ideantifier, field_id: &str, event: &StateMachineEvent) -> YoutrackResult<()> {
+    let path = format!("/api/issues/{}/fields/{}?fields=$type,id,value($type,id,name)", issue_id, field_id);
+    let body = serde_json::json!({
+        "$type": "StateMachineIssueCustomField",
+        "event": { "$type": "Event", "id": event.id, "presentation": event.presentation },
+    });
+    client.post_bytes(path, body).await?;
+    Ok(())
+}
+#[cfg(test)]
+mod tests {
+    use crate::rest_api::service::issues::SummaryUpdate;
+
+    #[test]
+    fn missing_description_is_kept() {
+        let update = SummaryUpdate { summary: "Login fails", description: None };
+        assert_eq!(serde_json::to_string(&update).unwrap(), r#"{"summary":"Login fails"}"#);
+    }
+}
@@ -1 +1,16 @@
-pub mod issues;
\ No newline at end of file
+pub mod issues;
+pub mod commands;
+pub mod watchers;
+pub mod articles;
+pub mod admin;
+pub mod versions;
+pub mod comments;
+pub mod users;
+pub mod time_tracking;
+
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+
+/// Encodes value for the query string, e.g. search queries
+pub(crate) fn encode(value: &str) -> String {
+    utf8_percent_encode(value, NON_ALPHANUMERIC).to_string()
+}
\ No newline at end of file
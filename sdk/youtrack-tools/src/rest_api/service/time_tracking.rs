@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::error::YoutrackResult;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DurationValue {
+    pub minutes: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AuthorRef {
+    pub id: String,
+}
+
+/// Spent time of an issue, the project must have time tracking enabled
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NewWorkItem {
+    pub duration: DurationValue,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Milliseconds since the epoch, today when missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<u64>,
+    /// Token user when missing, other authors need the "Update Work Item" permission
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<AuthorRef>,
+    pub uses_markdown: bool,
+}
+
+impl NewWorkItem {
+    pub fn new(minutes: u64) -> Self {
+        NewWorkItem { duration: DurationValue { minutes }, text: None, date: None, author: None, uses_markdown: true }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct WorkItemKey {
+    pub id: String,
+}
+
+pub async fn add_work_item(client: &HttpClient, issue_id: &str, item: NewWorkItem) -> YoutrackResult<WorkItemKey> {
+    let path = format!("/api/issues/{}/timeTracking/workItems?fields=id", issue_id);
+    let bytes = client.post_bytes(path, item).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
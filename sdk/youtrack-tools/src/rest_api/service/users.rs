@@ -0,0 +1,11 @@
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::error::YoutrackResult;
+use crate::rest_api::json_models::user::{UserDto, USER_FIELDS};
+
+/// All users visible to the token user, emails are empty without the "Read User" permission
+pub async fn fetch_users(client: &HttpClient) -> YoutrackResult<Vec<UserDto>> {
+    let uri = format!("/api/users?$top=-1&fields={}", USER_FIELDS);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
@@ -0,0 +1,92 @@
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::base::Ideantifier;
+use crate::rest_api::error::{YoutrackError, YoutrackResult};
+use crate::rest_api::json_models::admin::{BundleType, BundleElementDto};
+use crate::rest_api::service::admin::{fetch_project_custom_fields, fetch_bundle_values, add_bundle_value, update_bundle_value};
+use crate::rest_api::service::commands::{Command, execute_command, command_value};
+
+pub const FIX_VERSIONS_FIELD: &str = "Fix versions";
+
+#[derive(Debug, Clone, Default)]
+pub struct NewVersion {
+    pub name: String,
+    pub description: Option<String>,
+    /// Millis since epoch
+    pub release_date: Option<u64>,
+    pub released: bool,
+    pub archived: bool,
+}
+
+impl NewVersion {
+    pub fn new(name: &str) -> Self {
+        NewVersion { name: name.to_string(), ..NewVersion::default() }
+    }
+}
+
+/// Id of the version bundle used by the project field, e.g. `Fix versions`
+pub async fn fetch_version_bundle_id(client: &HttpClient, project_id: &str, field_name: &str) -> YoutrackResult<String> {
+    let fields = fetch_project_custom_fields(client, project_id).await?;
+    fields.into_iter()
+        .find(|field| field.field.name.as_deref().map(|name| name.eq_ignore_ascii_case(field_name)) == Some(true))
+        .and_then(|field| field.bundle)
+        .map(|bundle| bundle.id)
+        .ok_or_else(|| YoutrackError::FieldNotFound { project: project_id.to_string(), field: field_name.to_string() })
+}
+
+pub async fn find_version(client: &HttpClient, bundle_id: &str, name: &str) -> YoutrackResult<Option<BundleElementDto>> {
+    let versions: Vec<BundleElementDto> = fetch_bundle_values(client, BundleType::Version, bundle_id).await?;
+    Ok(versions.into_iter().find(|version| version.name == name))
+}
+
+pub async fn create_version(client: &HttpClient, bundle_id: &str, version: NewVersion) -> YoutrackResult<BundleElementDto> {
+    let value = BundleElementDto {
+        description: version.description,
+        release_date: version.release_date,
+        released: Some(version.released),
+        archived: Some(version.archived),
+        ..BundleElementDto::new(BundleType::Version, &version.name)
+    };
+    add_bundle_value(client, BundleType::Version, bundle_id, value).await
+}
+
+/// Returns existing version with the same name or creates a new one
+pub async fn ensure_version(client: &HttpClient, bundle_id: &str, version: NewVersion) -> YoutrackResult<BundleElementDto> {
+    match find_version(client, bundle_id, &version.name).await? {
+        Some(existing) => Ok(existing),
+        None => create_version(client, bundle_id, version).await,
+    }
+}
+
+/// `version` must be read from the bundle, its id is required
+pub async fn mark_released(client: &HttpClient, bundle_id: &str, version: &BundleElementDto, release_date: u64) -> YoutrackResult<BundleElementDto> {
+    let version_id = version.id.as_deref()
+        .ok_or_else(|| YoutrackError::MissingId(format!("version {}", version.name)))?;
+    let value = BundleElementDto {
+        released: Some(true),
+        release_date: Some(release_date),
+        ..BundleElementDto::new(BundleType::Version, &version.name)
+    };
+    update_bundle_value(client, BundleType::Version, bundle_id, version_id, value).await
+}
+
+/// Adds the version to the multi-version field of every issue with one command
+pub async fn add_version_to_issues(client: &HttpClient, field_name: &str, version_name: &str, issue_ids: &[Ideantifier]) -> YoutrackResult<()> {
+    if issue_ids.is_empty() {
+        return Ok(());
+    }
+    let mut command = Command::new(format!("{} {}", field_name, command_value(version_name)), issue_ids);
+    command.silent = true;
+    execute_command(client, command).await
+}
+
+/// Creates the version if it's missing, marks it released at `release_date` (millis) and adds it to the issues
+pub async fn release_version(client: &HttpClient, project_id: &str, field_name: &str, version_name: &str, release_date: u64, issue_ids: &[Ideantifier]) -> YoutrackResult<BundleElementDto> {
+    let bundle_id = fetch_version_bundle_id(client, project_id, field_name).await?;
+    let new_version = NewVersion { release_date: Some(release_date), released: true, ..NewVersion::new(version_name) };
+    let mut version = ensure_version(client, &bundle_id, new_version).await?;
+    if version.released != Some(true) {
+        version = mark_released(client, &bundle_id, &version, release_date).await?;
+    }
+    add_version_to_issues(client, field_name, &version.name, issue_ids).await?;
+    Ok(version)
+}
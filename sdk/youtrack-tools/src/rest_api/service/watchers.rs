@@ -0,0 +1,47 @@
+use serde::Serialize;
+use crate::rest_api::base::client::HttpClient;
+use crate::rest_api::base::Ideantifier;
+use crate::rest_api::error::YoutrackResult;
+use crate::rest_api::json_models::issue::{IssueWatchers, IssueVoters};
+use crate::rest_api::json_models::user::USER_FIELDS;
+use crate::rest_api::service::commands::{execute_command, Command};
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct StarUpdate {
+    has_star: bool,
+}
+
+#[derive(Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct VoteUpdate {
+    has_vote: bool,
+}
+
+pub async fn fetch_watchers(client: &HttpClient, issue_id: &Ideantifier) -> YoutrackResult<IssueWatchers> {
+    let uri = format!("/api/issues/{}/watchers?fields=hasStar,issueWatchers(isStarred,user({}))", issue_id, USER_FIELDS);
+    let bytes = client.get_bytes(uri).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Stars (watches) or unstars the issue for the token user
+pub async fn set_star(client: &HttpClient, issue_id: &Ideantifier, has_star: bool) -> YoutrackResult<IssueWatchers> {
+    let path = format!("/api/issues/{}/watchers?fields=hasStar", issue_id);
+    let bytes = client.post_bytes(path, StarUpdate { has_star }).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+pub async fn set_vote(client: &HttpClient, issue_id: &Ideantifier, has_vote: bool) -> YoutrackResult<IssueVoters> {
+    let path = format!("/api/issues/{}/voters?fields=hasVote", issue_id);
+    let bytes = client.post_bytes(path, VoteUpdate { has_vote }).await?;
+    Ok(serde_json::from_slice(&bytes)?)
+}
+
+/// Watchers of other users can be added only by commands, token user needs "Update Issue" permission
+pub async fn add_watchers(client: &HttpClient, issue_id: &Ideantifier, logins: &[String]) -> YoutrackResult<()> {
+    for login in logins {
+        let command = Command { silent: true, ..Command::new(format!("star {}", login), std::slice::from_ref(issue_id)) };
+        execute_command(client, command).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,279 @@
+use crate::rest_api::base::Ideantifier;
+use crate::rest_api::error::YoutrackError;
+use crate::rest_api::issue::Issue;
+use crate::rest_api::json_models::issue::IssueDto;
+use crate::rest_api::service::issues::persist_changes;
+
+/// Collects changes of several issues and saves them together.
+/// If one of the issues can't be saved, every issue sent before it (and the failed one when it was
+/// saved partially) is restored on the server from the `origin` snapshot of its `ActiveRecordWrap`.
+/// Restoring posts the whole snapshot, changes made by others after the snapshot are lost as well
+#[derive(Default)]
+pub struct UnitOfWork<'a> {
+    issues: Vec<&'a mut Issue>,
+}
+
+#[derive(Debug)]
+pub struct RollbackReport {
+    /// Issue which failed to save
+    pub failed: Ideantifier,
+    pub cause: YoutrackError,
+    /// Issues restored to their origin snapshot, in the order of restoring
+    pub rolled_back: Vec<Ideantifier>,
+    /// Issues which couldn't be restored and stay modified on the server
+    pub not_rolled_back: Vec<(Ideantifier, YoutrackError)>,
+    /// Issues which weren't sent to the server at all
+    pub skipped: Vec<Ideantifier>,
+}
+
+impl<'a> UnitOfWork<'a> {
+    pub fn new() -> Self {
+        UnitOfWork { issues: Vec::new() }
+    }
+
+    pub fn register(&mut self, issue: &'a mut Issue) -> &mut Self {
+        self.issues.push(issue);
+        self
+    }
+
+    /// Saves all registered issues. On success every issue is refreshed with the server state,
+    /// on failure local records stay untouched, so changes can be retried
+    pub async fn commit(self) -> Result<(), RollbackReport> {
+        let mut issues = self.issues;
+        let mut saved: Vec<(usize, IssueDto)> = Vec::with_capacity(issues.len());
+
+        for (index, issue) in issues.iter().enumerate() {
+            if !issue.is_dirty() {
+                continue;
+            }
+            match persist_changes(&issue.http_client, issue.origin.clone(), issue.inner.clone()).await {
+                Ok(new_origin) => saved.push((index, new_origin)),
+                Err(cause) => {
+                    log::warn!("Issue {} wasn't saved, rolling back: {}", issue.origin.id, cause);
+                    let dirty: Vec<bool> = issues.iter().map(|issue| issue.is_dirty()).collect();
+                    let saved: Vec<usize> = saved.iter().map(|(index, _)| *index).collect();
+                    let partially_saved = matches!(cause, YoutrackError::PartiallySaved { .. });
+                    let (restore, skipped) = rollback_plan(&dirty, &saved, index, partially_saved);
+                    let mut report = RollbackReport {
+                        failed: issue.origin.id.clone(),
+                        cause,
+                        rolled_back: Vec::new(),
+                        not_rolled_back: Vec::new(),
+                        skipped: skipped.into_iter().map(|index| issues[index].origin.id.clone()).collect(),
+                    };
+                    rollback(&issues, restore, &mut report).await;
+                    return Err(report);
+                }
+            }
+        }
+
+        for (index, new_origin) in saved {
+            issues[index].refresh(new_origin);
+        }
+        Ok(())
+    }
+}
+
+/// Indexes of the issues to restore in the order of restoring (reverse order of saving, the failed one first
+/// when some of its requests went out) and indexes of the dirty issues after the failed one, which weren't sent
+fn rollback_plan(dirty: &[bool], saved: &[usize], failed: usize, partially_saved: bool) -> (Vec<usize>, Vec<usize>) {
+    let restore = Some(failed).filter(|_| partially_saved).into_iter().chain(saved.iter().rev().copied()).collect();
+    let skipped = (failed + 1..dirty.len()).filter(|index| dirty[*index]).collect();
+    (restore, skipped)
+}
+
+/// Re-applies origin snapshots
+async fn rollback(issues: &[&mut Issue], restore: Vec<usize>, report: &mut RollbackReport) {
+    for index in restore {
+        let issue = &issues[index];
+        let issue_id = issue.origin.id.clone();
+        // compensating POST overwrites concurrent edits
+        match persist_changes(&issue.http_client, issue.inner.clone(), issue.origin.clone()).await {
+            Ok(_) => report.rolled_back.push(issue_id),
+            Err(error) => {
+                log::error!("Issue {} wasn't rolled back: {}", issue_id, error);
+                report.not_rolled_back.push((issue_id, error))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use serde_json::{json, Value};
+    use crate::rest_api::base::client::HttpClient;
+    use crate::rest_api::client::Config;
+    use crate::rest_api::error::YoutrackError;
+    use crate::rest_api::issue::Issue;
+    use crate::rest_api::unit_of_work::{rollback_plan, UnitOfWork};
+
+    type Requests = Arc<Mutex<Vec<String>>>;
+
+    /// Issue fixture with another id and state
+    fn issue_json(id: &str, state: &str) -> Value {
+        let mut issue: Value = serde_json::from_str(include_str!("json_models/issue.json")).unwrap();
+        issue["id"] = json!(id);
+        issue["idReadable"] = json!(format!("SSP-{}", id));
+        let fields = issue["fields"].as_array_mut().unwrap();
+        let state_field = fields.iter_mut().find(|field| field["name"] == "State").unwrap();
+        state_field["value"]["name"] = json!(state);
+        issue
+    }
+
+    fn issue(host: &str, id: &str) -> Issue {
+        let config = Config::new(host.to_string(), "perm:dGVzdA==.MS0x.xxxx".to_string()).with_proxy(None);
+        Issue::new(HttpClient::new(config), serde_json::from_value(issue_json(id, "Open")).unwrap())
+    }
+
+    /// Answers every request with `respond(method, path)`, requests are recorded as `POST /api/issues/2-1`
+    fn serve(respond: impl Fn(&str, &str) -> (u16, Value) + Send + 'static) -> (String, Requests) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let host = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Requests::default();
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut content_length = 0;
+                for header in reader.by_ref().lines().map(Result::unwrap).take_while(|line| !line.is_empty()) {
+                    if let Some(length) = header.to_lowercase().strip_prefix("content-length:") {
+                        content_length = length.trim().parse().unwrap();
+                    }
+                }
+                reader.take(content_length).read_to_end(&mut Vec::new()).unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap().to_string();
+                let path = parts.next().unwrap().split('?').next().unwrap().to_string();
+                let (status, body) = respond(&method, &path);
+                recorded.lock().unwrap().push(format!("{} {}", method, path));
+                let body = body.to_string();
+                write!(stream, "HTTP/1.1 {} Status\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                       status, body.len(), body).unwrap();
+            }
+        });
+        (host, requests)
+    }
+
+    fn state_values() -> Value {
+        json!([
+            { "$type": "StateBundleElement", "isResolved": false, "archived": false, "name": "Open", "id": "97-13" },
+            { "$type": "StateBundleElement", "isResolved": false, "archived": false, "name": "In Progress", "id": "97-14" },
+        ])
+    }
+
+    #[test]
+    fn issues_are_refreshed_with_the_state_after_the_last_request() {
+        let (host, requests) = serve(|method, path| match (method, path) {
+            ("GET", "/api/admin/customFieldSettings/bundles/state/96-1/values") => (200, state_values()),
+            // the body response still has the old state
+            ("POST", "/api/issues/2-1") => (200, issue_json("2-1", "Open")),
+            ("POST", "/api/issues/2-1/fields/107-14") => (200, json!({})),
+            ("GET", "/api/issues/2-1") => (200, issue_json("2-1", "In Progress")),
+            _ => (404, json!({})),
+        });
+        let mut issue = issue(&host, "2-1");
+        issue.set_state_name("In Progress".to_string()).unwrap();
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            let mut unit_of_work = UnitOfWork::new();
+            unit_of_work.register(&mut issue);
+            unit_of_work.commit().await.unwrap();
+        });
+        assert_eq!(issue.origin.get_state().unwrap().state_name(), "In Progress");
+        assert!(!issue.is_dirty());
+        assert_eq!(*requests.lock().unwrap(), vec![
+            "GET /api/admin/customFieldSettings/bundles/state/96-1/values",
+            "POST /api/issues/2-1",
+            "POST /api/issues/2-1/fields/107-14",
+            "GET /api/issues/2-1",
+        ]);
+    }
+
+    #[test]
+    fn issues_without_sent_requests_are_not_rolled_back() {
+        let (host, requests) = serve(|method, path| match (method, path) {
+            ("POST", "/api/issues/2-1") => (200, issue_json("2-1", "Open")),
+            ("POST", "/api/issues/2-2") => (500, json!({ "error": "server_error" })),
+            _ => (404, json!({})),
+        });
+        let mut issues: Vec<Issue> = ["2-1", "2-2", "2-3"].iter().map(|id| issue(&host, id)).collect();
+        for issue in issues.iter_mut() {
+            issue.summary = Some("Changed".to_string());
+        }
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let report = runtime.block_on(async {
+            let mut unit_of_work = UnitOfWork::new();
+            for issue in issues.iter_mut() {
+                unit_of_work.register(issue);
+            }
+            unit_of_work.commit().await.unwrap_err()
+        });
+        assert_eq!(report.failed, "2-2");
+        assert_eq!(report.rolled_back, vec!["2-1".to_string()]);
+        assert_eq!(report.skipped, vec!["2-3".to_string()]);
+        assert_eq!(*requests.lock().unwrap(), vec!["POST /api/issues/2-1", "POST /api/issues/2-2", "POST /api/issues/2-1"]);
+        assert!(issues.iter().all(|issue| issue.is_dirty()));
+    }
+
+    #[test]
+    fn partially_saved_issue_is_rolled_back() {
+        let state_posts = AtomicUsize::new(0);
+        let (host, requests) = serve(move |method, path| match (method, path) {
+            ("GET", "/api/admin/customFieldSettings/bundles/state/96-1/values") => (200, state_values()),
+            ("POST", "/api/issues/2-1") => (200, issue_json("2-1", "Open")),
+            ("POST", "/api/issues/2-2") => (200, issue_json("2-2", "Open")),
+            // only the first state update fails, the one of the rollback goes through
+            ("POST", "/api/issues/2-2/fields/107-14") if state_posts.fetch_add(1, Ordering::SeqCst) == 0 => (500, json!({})),
+            ("POST", "/api/issues/2-2/fields/107-14") => (200, json!({})),
+            ("GET", "/api/issues/2-2") => (200, issue_json("2-2", "Open")),
+            _ => (404, json!({})),
+        });
+        let mut first = issue(&host, "2-1");
+        first.summary = Some("Changed".to_string());
+        let mut second = issue(&host, "2-2");
+        second.set_state_name("In Progress".to_string()).unwrap();
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let report = runtime.block_on(async {
+            let mut unit_of_work = UnitOfWork::new();
+            unit_of_work.register(&mut first).register(&mut second);
+            unit_of_work.commit().await.unwrap_err()
+        });
+        assert_eq!(report.failed, "2-2");
+        assert!(matches!(report.cause, YoutrackError::PartiallySaved { .. }), "{}", report.cause);
+        assert_eq!(report.rolled_back, vec!["2-2".to_string(), "2-1".to_string()]);
+        assert!(report.not_rolled_back.is_empty());
+        assert_eq!(requests.lock().unwrap().iter().filter(|request| request.as_str() == "POST /api/issues/2-2/fields/107-14").count(), 2);
+    }
+
+    #[test]
+    fn partially_saved_issue_is_restored_first() {
+        let (restore, _) = rollback_plan(&[true, true, true, true], &[0, 1, 2], 3, true);
+        assert_eq!(restore, vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn clean_issues_are_neither_restored_nor_skipped() {
+        let dirty = [true, false, true, true, false, true];
+        let (restore, skipped) = rollback_plan(&dirty, &[0, 2], 3, false);
+        assert_eq!(restore, vec![2, 0]);
+        assert_eq!(skipped, vec![5]);
+    }
+
+    #[test]
+    fn first_issue_fails() {
+        let (restore, skipped) = rollback_plan(&[true, true], &[], 0, false);
+        assert!(restore.is_empty());
+        assert_eq!(skipped, vec![1]);
+    }
+}
@@ -4,6 +4,11 @@ var entities = require('@jetbrains/youtrack-scripting-api/entities');
 var http = require('@jetbrains/youtrack-scripting-api/http');
 var workflow = require('@jetbrains/youtrack-scripting-api/workflow');
 
+// `web-app serve`, see the webhook section of settings.yaml
+var SERVICE_URL = 'http://web-app:8000';
+// value of the GITLAB_WEBHOOK_SECRET variable of the service
+var SECRET = '';
+
 exports.rule = entities.Issue.onChange({
     title: workflow.i18n('Call service when status was changed'),
     guard: function(ctx) {
@@ -14,13 +19,17 @@ exports.rule = entities.Issue.onChange({
         var issue = ctx.issue;
         var oldValue = issue.fields.oldValue(ctx.State);
 
-        console.log(issue.fields.State.name);
-        console.log(oldValue.name);
-
-        var connection = new http.Connection("https://fecda0bb920bb5b77d1d34e59c5d0229.m.pipedream.net", null, 2000);
-        var response = connection.postSync('', null, JSON.stringify(ctx));
+        // the service creates the branch of an issue which goes In Progress
+        var connection = new http.Connection(SERVICE_URL, null, 2000);
+        connection.addHeader('Content-Type', 'application/json');
+        connection.addHeader('X-Youtrack-Token', SECRET);
+        var response = connection.postSync('/youtrack/state', null, JSON.stringify({
+            issue: issue.id,
+            state: issue.fields.State.name,
+            oldState: oldValue ? oldValue.name : null
+        }));
         if (!response.isSuccess) {
-            console.warn('Failed to post notification to Slack. Details: ' + response.toString());
+            console.warn('Failed to post the state change of ' + issue.id + '. Details: ' + response.toString());
         }
     },
     requirements: {
@@ -29,5 +38,3 @@ exports.rule = entities.Issue.onChange({
         }
     }
 });
-
-